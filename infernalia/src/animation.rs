@@ -0,0 +1,92 @@
+use std::f32::consts::TAU;
+
+use crate::procedural;
+use crate::texture::{Texture, Wrap};
+
+// Texturas animadas: una secuencia de fotogramas, un desplazamiento continuo de
+// las coordenadas de textura, o las dos cosas a la vez. El bucle del juego las
+// avanza con `update` y los renderizadores leen el fotograma y desplazamiento actuales.
+
+pub struct AnimatedTexture {
+    frames: Vec<Texture>,
+    frame_time: f32,    // Segundos que dura cada fotograma
+    scroll: (f32, f32), // Texturas completas que se desplaza por segundo en u y v
+    time: f32,
+}
+
+impl AnimatedTexture {
+    pub fn still(texture: Texture) -> Self {
+        AnimatedTexture::from_frames(vec![texture], 1.0)
+    }
+
+    pub fn from_frames(frames: Vec<Texture>, fps: f32) -> Self {
+        AnimatedTexture { frames, frame_time: 1.0 / fps, scroll: (0.0, 0.0), time: 0.0 }
+    }
+
+    pub fn scrolling(texture: Texture, speed_u: f32, speed_v: f32) -> Self {
+        AnimatedTexture { scroll: (speed_u, speed_v), ..AnimatedTexture::still(texture) }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.time += delta_time;
+    }
+
+    pub fn frame(&self) -> &Texture {
+        let index = (self.time / self.frame_time) as usize % self.frames.len();
+        &self.frames[index]
+    }
+
+    // Desplazamiento actual en texels del fotograma, para sumarlo antes de aplicar el módulo
+    pub fn scroll_texels(&self) -> (usize, usize) {
+        let frame = self.frame();
+        let u = (self.scroll.0 * self.time).rem_euclid(1.0);
+        let v = (self.scroll.1 * self.time).rem_euclid(1.0);
+        ((u * frame.width as f32) as usize, (v * frame.height as f32) as usize)
+    }
+
+    // Color en las coordenadas (u, v); se repite fuera de 0.0..1.0
+    pub fn sample(&self, u: f32, v: f32) -> u32 {
+        let (scroll_u, scroll_v) = (self.scroll.0 * self.time, self.scroll.1 * self.time);
+        self.frame().sample(u + scroll_u, v + scroll_v, Wrap::Repeat, false)
+    }
+}
+
+const SIZE: usize = 64;
+
+// Lava que fluye: vetas brillantes sobre roca fundida, se desplaza en diagonal
+pub fn lava(seed: u32) -> AnimatedTexture {
+    AnimatedTexture::scrolling(procedural::lava(SIZE, seed), 0.05, 0.03)
+}
+
+// Fango que se agita despacio
+pub fn mud(seed: u32) -> AnimatedTexture {
+    AnimatedTexture::scrolling(procedural::mud(SIZE, seed), 0.01, 0.02)
+}
+
+// Hielo quieto
+pub fn ice(seed: u32) -> AnimatedTexture {
+    AnimatedTexture::still(procedural::ice(SIZE, seed))
+}
+
+// Paredes de carne que laten: venas oscuras y un pulso de brillo repartido en ocho fotogramas
+pub fn flesh() -> AnimatedTexture {
+    let frames = (0..8)
+        .map(|frame| {
+            let pulse = 0.8 + 0.2 * (frame as f32 / 8.0 * TAU).sin();
+            let mut data = Vec::with_capacity(SIZE * SIZE);
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    let (u, v) = (x as f32 / SIZE as f32 * TAU, y as f32 / SIZE as f32 * TAU);
+                    let vein = ((u * 3.0 + (v * 2.0).sin() * 1.5).sin() * (v * 2.0 + (u * 4.0).sin()).cos()).abs();
+                    let tissue = if vein < 0.12 { 0.45 } else { 0.85 + 0.15 * (u * 7.0 + v * 5.0).sin() };
+                    let r = (170.0 * tissue * pulse) as u32;
+                    let g = (55.0 * tissue * pulse) as u32;
+                    let b = (60.0 * tissue * pulse) as u32;
+                    data.push(0xFF000000 | (r << 16) | (g << 8) | b);
+                }
+            }
+            Texture::new(SIZE, SIZE, data)
+        })
+        .collect();
+    AnimatedTexture::from_frames(frames, 6.0)
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use rusttype::Font;
+
+use crate::sfx::Sound;
+use crate::texture::Texture;
+use crate::theme::Theme;
+
+// Recursos del juego (texturas, fuentes, sonidos y archivos de datos) buscados por nombre
+// relativo a la carpeta de recursos, p. ej. "music/steps.mp3". Las carpetas se prueban en
+// orden: la de la variable de entorno INFERNALIA_ASSETS, `assets` junto al ejecutable,
+// `assets` en el directorio actual y, por último, `src/assets` del propio crate (así
+// `cargo run` funciona desde cualquier carpeta). Lo que ya se cargó se guarda y no se vuelve a leer.
+// Si falta una textura se usa un tablero de ajedrez magenta y si falta un sonido, silencio,
+// avisando por consola en los dos casos. Con un tema elegido (ver `theme.rs`), lo que trae
+// el tema manda sobre todo lo demás.
+//
+// Con la feature `embed-assets` los recursos por defecto (los de `src/assets`) van dentro
+// del ejecutable y se usan cuando no están en ninguna carpeta.
+
+pub const ROOT_VARIABLE: &str = "INFERNALIA_ASSETS";
+
+#[cfg(feature = "embed-assets")]
+const EMBEDDED: &[(&str, &[u8])] = &[
+    ("textures/walls.jpg", include_bytes!("assets/textures/walls.jpg")),
+    ("textures/floor.jpg", include_bytes!("assets/textures/floor.jpg")),
+    ("textures/sky.jpg", include_bytes!("assets/textures/sky.jpg")),
+    ("fonts/AGaramondPro-Regular.otf", include_bytes!("assets/fonts/AGaramondPro-Regular.otf")),
+    ("music/ambience.mp3", include_bytes!("assets/music/ambience.mp3")),
+    ("music/steps.mp3", include_bytes!("assets/music/steps.mp3")),
+];
+
+#[cfg(not(feature = "embed-assets"))]
+const EMBEDDED: &[(&str, &[u8])] = &[];
+
+pub struct Assets {
+    roots: Vec<PathBuf>,
+    theme: Option<Theme>,
+    textures: HashMap<String, Rc<Texture>>,
+    fonts: HashMap<String, Option<Rc<Font<'static>>>>,
+    sounds: HashMap<String, Sound>,
+}
+
+impl Assets {
+    pub fn new() -> Self {
+        let mut roots = Vec::new();
+        if let Ok(root) = env::var(ROOT_VARIABLE) {
+            roots.push(PathBuf::from(root));
+        }
+        if let Some(folder) = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+            roots.push(folder.join("assets"));
+        }
+        roots.push(PathBuf::from("./assets"));
+        roots.push(PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src/assets")));
+        Assets::with_roots(roots)
+    }
+
+    pub fn with_roots(roots: Vec<PathBuf>) -> Self {
+        let found = roots.iter().find(|root| root.is_dir());
+        match found {
+            Some(root) => println!("Assets folder: {}", root.display()),
+            None => println!("No assets folder found (tried {:?}); using built-in assets.", roots),
+        }
+        Assets { roots, theme: None, textures: HashMap::new(), fonts: HashMap::new(), sounds: HashMap::new() }
+    }
+
+    // Cambia de tema; lo ya cargado se olvida para que se vuelva a leer del tema nuevo
+    pub fn set_theme(&mut self, theme: Option<Theme>) {
+        if let Some(theme) = &theme {
+            println!("Theme: {}{}", theme.name, theme.author.as_ref().map_or(String::new(), |author| format!(" by {}", author)));
+            if let Some(description) = &theme.description {
+                println!("  {}", description);
+            }
+        }
+        self.theme = theme;
+        self.textures.clear();
+        self.fonts.clear();
+        self.sounds.clear();
+    }
+
+    // Ruta de un recurso en la primera carpeta que lo tenga; si no está en ninguna, donde
+    // debería estar en la primera carpeta que existe (sirve para archivos que se escriben) y,
+    // si no existe ninguna (todo incluido en el ejecutable), en la carpeta temporal
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.find(name)
+            .or_else(|| self.roots.iter().find(|root| root.is_dir()).map(|root| root.join(name)))
+            .unwrap_or_else(|| env::temp_dir().join(name))
+    }
+
+    fn find(&self, name: &str) -> Option<PathBuf> {
+        self.roots.iter().map(|root| root.join(name)).find(|path| path.exists())
+    }
+
+    // Contenido de un recurso: del tema, del disco o, si no está, de los incluidos en el ejecutable
+    pub fn read(&self, name: &str) -> Option<Vec<u8>> {
+        if let Some(bytes) = self.theme.as_ref().and_then(|theme| theme.read(name)) {
+            return Some(bytes);
+        }
+        if let Some(path) = self.find(name) {
+            match fs::read(&path) {
+                Ok(bytes) => return Some(bytes),
+                Err(e) => println!("Failed to read {}: {}", path.display(), e),
+            }
+        }
+        EMBEDDED.iter().find(|(embedded, _)| *embedded == name).map(|(_, bytes)| bytes.to_vec())
+    }
+
+    // Si el recurso está en el tema, en alguna carpeta o dentro del ejecutable
+    pub fn exists(&self, name: &str) -> bool {
+        self.theme.as_ref().is_some_and(|theme| theme.read(name).is_some())
+            || self.find(name).is_some()
+            || EMBEDDED.iter().any(|(embedded, _)| *embedded == name)
+    }
+
+    pub fn read_text(&self, name: &str) -> Option<String> {
+        self.read(name).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    // Recursos de la carpeta `folder` ("textures/walls.jpg"...) de todas las procedencias,
+    // de menos a más prioritaria: los incluidos, las carpetas en orden inverso y el tema.
+    // Puede haber repetidos.
+    pub fn list(&self, folder: &str) -> Vec<String> {
+        let prefix = format!("{}/", folder.trim_end_matches('/'));
+        let mut names: Vec<String> = EMBEDDED
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .filter(|name| name.strip_prefix(&prefix).is_some_and(|file| !file.contains('/')))
+            .collect();
+        for root in self.roots.iter().rev() {
+            if let Ok(entries) = fs::read_dir(root.join(folder)) {
+                names.extend(entries.flatten().filter_map(|entry| entry.file_name().to_str().map(|file| format!("{}{}", prefix, file))));
+            }
+        }
+        if let Some(theme) = &self.theme {
+            names.extend(theme.list(folder));
+        }
+        names
+    }
+
+    // La textura, o None si no está o no se puede leer
+    pub fn try_texture(&mut self, name: &str) -> Option<Rc<Texture>> {
+        if let Some(texture) = self.textures.get(name) {
+            return Some(Rc::clone(texture));
+        }
+        let texture = match Texture::load_from_memory(&self.read(name)?) {
+            Ok(texture) => Rc::new(texture),
+            Err(e) => {
+                println!("{}: {}", name, e);
+                return None;
+            }
+        };
+        self.textures.insert(name.to_string(), Rc::clone(&texture));
+        Some(texture)
+    }
+
+    // La textura, o un tablero de ajedrez bien visible si falta
+    pub fn texture(&mut self, name: &str) -> Rc<Texture> {
+        self.try_texture(name).unwrap_or_else(|| {
+            println!("Missing texture {}; using a checkerboard.", name);
+            let texture = Rc::new(checkerboard());
+            self.textures.insert(name.to_string(), Rc::clone(&texture));
+            texture
+        })
+    }
+
+    // La fuente, o None si falta (el texto que la usa no se dibuja)
+    pub fn font(&mut self, name: &str) -> Option<Rc<Font<'static>>> {
+        if let Some(font) = self.fonts.get(name) {
+            return font.clone();
+        }
+        let font = self.read(name).and_then(Font::try_from_vec).map(Rc::new);
+        if font.is_none() {
+            println!("Missing or invalid font {}; text using it will not be drawn.", name);
+        }
+        self.fonts.insert(name.to_string(), font.clone());
+        font
+    }
+
+    // El sonido, o silencio si falta
+    pub fn sound(&mut self, name: &str) -> Sound {
+        if let Some(sound) = self.sounds.get(name) {
+            return sound.clone();
+        }
+        let sound = match self.read(name) {
+            Some(bytes) => Sound::new(bytes),
+            None => {
+                println!("Missing sound {}; it will be silent.", name);
+                Sound::silence()
+            }
+        };
+        self.sounds.insert(name.to_string(), sound.clone());
+        sound
+    }
+}
+
+// Magenta y negro en casillas de 8 texels, para que una textura que falta se note
+fn checkerboard() -> Texture {
+    let size = 64;
+    let data = (0..size * size).map(|i| if ((i % size) / 8 + (i / size) / 8) % 2 == 0 { 0xFFFF00FF } else { 0xFF000000 }).collect();
+    Texture::new(size, size, data)
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::animation::AnimatedTexture;
+use crate::assets::Assets;
+use crate::color;
+use crate::sprite::AnimatedSprite;
+use crate::texture::Texture;
+
+// Atlas de texturas: una sola imagen con regiones con nombre (paredes, sprites,
+// iconos del HUD), secuencias de fotogramas y una fuente de mapa de bits, descritas
+// en un archivo JSON o RON junto a la imagen. Por ejemplo, en RON:
+//
+//     (
+//         image: "atlas.png",
+//         color_key: Some("FF00FF"),
+//         regions: {
+//             "walls": (x: 0, y: 0, w: 128, h: 128),
+//             "key_red": (x: 128, y: 0, w: 16, h: 16),
+//         },
+//         sequences: {
+//             "torch": (x: 0, y: 128, w: 64, h: 64, count: 4, frame_time: 0.12),
+//         },
+//         font: Some((chars: "0123456789:", x: 0, y: 192, w: 8, h: 8, columns: 11)),
+//     )
+//
+// Los fotogramas de una secuencia van seguidos de izquierda a derecha desde (x, y) y
+// los caracteres de la fuente en filas de `columns` celdas. El JSON usa los mismos
+// campos, con `"font": null` o sin él si no hay fuente. `color_key` es opcional: los
+// píxeles de ese color se vuelven transparentes, para imágenes sin canal alfa.
+
+#[derive(Deserialize)]
+struct Description {
+    image: String,
+    #[serde(default)]
+    color_key: Option<String>,
+    #[serde(default)]
+    regions: HashMap<String, Region>,
+    #[serde(default)]
+    sequences: HashMap<String, Sequence>,
+    #[serde(default)]
+    font: Option<FontGrid>,
+}
+
+#[derive(Deserialize)]
+struct Region {
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+}
+
+#[derive(Deserialize)]
+struct Sequence {
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    count: usize,
+    frame_time: f32,
+}
+
+#[derive(Deserialize)]
+struct FontGrid {
+    chars: String,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    columns: usize,
+}
+
+#[derive(Default)]
+pub struct Atlas {
+    regions: HashMap<String, Texture>,
+    sequences: HashMap<String, (Vec<Texture>, f32)>,
+    glyphs: HashMap<char, Texture>,
+}
+
+const DESCRIPTIONS: [&str; 2] = ["atlas.ron", "atlas.json"];
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "bmp"];
+
+impl Atlas {
+    // Lee una descripción de atlas de los recursos (p. ej. "textures/atlas.ron"); el formato
+    // se elige por la extensión (.ron o .json) y la imagen se busca junto a ella
+    pub fn load(assets: &mut Assets, description: &str) -> Result<Atlas, String> {
+        let text = assets.read_text(description).ok_or_else(|| format!("Failed to read atlas {}", description))?;
+        let parsed: Description = match Path::new(description).extension().and_then(|extension| extension.to_str()) {
+            Some("ron") => ron::from_str(&text).map_err(|e| format!("Invalid atlas {}: {}", description, e))?,
+            _ => serde_json::from_str(&text).map_err(|e| format!("Invalid atlas {}: {}", description, e))?,
+        };
+
+        let image_name = match description.rfind('/') {
+            Some(slash) => format!("{}{}", &description[..=slash], parsed.image),
+            None => parsed.image.clone(),
+        };
+        let image = assets.try_texture(&image_name).ok_or_else(|| format!("Atlas {}: missing image {}", description, image_name))?;
+        let mut image = Texture::clone(&image);
+        if let Some(key) = &parsed.color_key {
+            let key = color::parse_hex(key).ok_or_else(|| format!("Invalid atlas {}: bad color_key '{}'", description, key))?;
+            image = image.with_color_key(key);
+        }
+        let mut atlas = Atlas::default();
+
+        for (name, r) in parsed.regions {
+            let texture = image.crop(r.x, r.y, r.w, r.h).map_err(|e| format!("Atlas region '{}': {}", name, e))?;
+            atlas.regions.insert(name, texture);
+        }
+        for (name, s) in parsed.sequences {
+            let frames = (0..s.count)
+                .map(|i| image.crop(s.x + i * s.w, s.y, s.w, s.h))
+                .collect::<Result<Vec<Texture>, String>>()
+                .map_err(|e| format!("Atlas sequence '{}': {}", name, e))?;
+            atlas.sequences.insert(name, (frames, s.frame_time));
+        }
+        if let Some(font) = parsed.font {
+            for (i, c) in font.chars.chars().enumerate() {
+                let (column, row) = (i % font.columns.max(1), i / font.columns.max(1));
+                let glyph = image.crop(font.x + column * font.w, font.y + row * font.h, font.w, font.h)
+                    .map_err(|e| format!("Atlas glyph '{}': {}", c, e))?;
+                atlas.glyphs.insert(c, glyph);
+            }
+        }
+
+        Ok(atlas)
+    }
+
+    // Todas las texturas de una carpeta de los recursos (incluido el tema elegido): cada
+    // imagen suelta es una región con el nombre del archivo sin extensión, y si hay un
+    // atlas.ron o atlas.json sus regiones se añaden (y mandan sobre las sueltas del mismo nombre)
+    pub fn from_assets(assets: &mut Assets, folder: &str) -> Atlas {
+        let mut atlas = Atlas::default();
+
+        // De menos a más prioritario, así que la última imagen con un nombre es la que queda
+        for name in assets.list(folder) {
+            let path = Path::new(&name);
+            let is_image = path.extension().and_then(|e| e.to_str()).is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+            let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()).filter(|_| is_image) else {
+                continue;
+            };
+            match assets.try_texture(&name) {
+                Some(texture) => {
+                    atlas.regions.insert(stem.to_string(), Texture::clone(&texture));
+                }
+                None => println!("Skipping {}", name),
+            }
+        }
+
+        let descriptions = DESCRIPTIONS.iter().map(|file| format!("{}/{}", folder, file));
+        if let Some(description) = descriptions.into_iter().find(|name| assets.read(name).is_some()) {
+            match Atlas::load(assets, &description) {
+                Ok(packed) => {
+                    atlas.regions.extend(packed.regions);
+                    atlas.sequences.extend(packed.sequences);
+                    atlas.glyphs.extend(packed.glyphs);
+                }
+                Err(e) => println!("{}", e),
+            }
+        }
+
+        println!("{} textures, {} sequences and {} glyphs loaded from {}.", atlas.regions.len(), atlas.sequences.len(), atlas.glyphs.len(), folder);
+        atlas
+    }
+
+    pub fn texture(&self, name: &str) -> Option<&Texture> {
+        self.regions.get(name)
+    }
+
+    // Textura animada con los fotogramas de una secuencia, o fija si solo hay una región con ese nombre
+    pub fn animated_texture(&self, name: &str) -> Option<AnimatedTexture> {
+        match self.sequences.get(name) {
+            Some((frames, frame_time)) => Some(AnimatedTexture::from_frames(frames.clone(), 1.0 / frame_time)),
+            None => self.texture(name).map(|texture| AnimatedTexture::still(texture.clone())),
+        }
+    }
+
+    // Igual, pero como sprite
+    pub fn sprite(&self, name: &str) -> Option<AnimatedSprite> {
+        match self.sequences.get(name) {
+            Some((frames, frame_time)) => Some(AnimatedSprite::new(frames.clone(), *frame_time)),
+            None => self.texture(name).map(|texture| AnimatedSprite::new(vec![texture.clone()], 1.0)),
+        }
+    }
+
+    pub fn glyph(&self, c: char) -> Option<&Texture> {
+        self.glyphs.get(&c)
+    }
+
+    pub fn has_font(&self) -> bool {
+        !self.glyphs.is_empty()
+    }
+}
@@ -0,0 +1,84 @@
+use serde::Deserialize;
+
+use crate::assets::Assets;
+use crate::color;
+use crate::fog::{Fog, FogMode};
+use crate::maze::MazeStyle;
+use crate::sfx::Sound;
+
+// La campaña: los nueve círculos del infierno, de Limbo a Traición, uno por nivel. Cada
+// círculo tiene sus texturas, su niebla, su música de fondo, su algoritmo de laberinto y
+// su tipo de charco. Se describen en `campaign.ron` (de los recursos o del tema elegido);
+// si falta o no se puede leer se usa la copia incluida en el ejecutable.
+
+const FILE: &str = "campaign.ron";
+const DEFAULT_AMBIENT: &str = "music/ambience.mp3";
+const BUILT_IN: &str = include_str!("assets/campaign.ron");
+
+#[derive(Deserialize)]
+pub struct Campaign {
+    pub circles: Vec<Circle>,
+}
+
+#[derive(Deserialize)]
+pub struct Circle {
+    pub name: String,
+    pub subtitle: String,
+    pub walls: Surface,
+    pub floor: Surface,
+    pub sky: Surface,
+    #[serde(default)]
+    pub tint: Option<String>,
+    pub fog: FogStyle,
+    pub ambient: String, // Nombre del recurso, p. ej. "music/ambience.mp3"
+    #[serde(default)]
+    pub maze: MazeStyle,
+}
+
+// Textura de una superficie: la imagen `image` si existe o si no la generada `generate`
+// (un nombre de `procedural::by_name`)
+#[derive(Deserialize)]
+pub struct Surface {
+    #[serde(default)]
+    pub image: Option<String>,
+    pub generate: String,
+}
+
+#[derive(Deserialize)]
+pub struct FogStyle {
+    pub color: String,
+    pub density: f32,
+}
+
+impl Campaign {
+    pub fn load(assets: &Assets) -> Campaign {
+        let loaded = match assets.read_text(FILE) {
+            Some(text) => ron::from_str::<Campaign>(&text).map_err(|e| format!("Invalid {}: {}", FILE, e)),
+            None => Err(format!("No {} found", FILE)),
+        };
+        match loaded.and_then(|campaign| if campaign.circles.is_empty() { Err(format!("{} has no circles", FILE)) } else { Ok(campaign) }) {
+            Ok(campaign) => campaign,
+            Err(e) => {
+                println!("{}; using the built-in campaign.", e);
+                ron::from_str(BUILT_IN).expect("Invalid built-in campaign")
+            }
+        }
+    }
+}
+
+impl Circle {
+    pub fn tint(&self) -> Option<u32> {
+        self.tint.as_deref().and_then(color::parse_hex)
+    }
+
+    // La música del círculo, o la de siempre si el círculo no tiene la suya
+    pub fn ambient_track(&self, assets: &mut Assets) -> Sound {
+        let name = if assets.exists(&self.ambient) { &self.ambient } else { DEFAULT_AMBIENT };
+        assets.sound(name)
+    }
+
+    pub fn fog(&self) -> Fog {
+        let fog_color = color::parse_hex(&self.fog.color).unwrap_or(Fog::hellish().color);
+        Fog::new(FogMode::Exponential { density: self.fog.density }, fog_color)
+    }
+}
@@ -0,0 +1,44 @@
+// Utilidades de color en punto fijo para los bucles por píxel del renderizador.
+// Los factores van de 0 a 256 (256 = 1.0) y los canales R y B se procesan juntos
+// con la máscara 0xFF00FF para evitar trabajar con flotantes.
+
+pub const ONE: u32 = 256;
+
+// Escala un color por `factor` (0..=256).
+pub fn scale(color: u32, factor: u32) -> u32 {
+    let rb = (((color & 0xFF00FF) * factor) >> 8) & 0xFF00FF;
+    let g = (((color & 0x00FF00) * factor) >> 8) & 0x00FF00;
+    (color & 0xFF000000) | rb | g
+}
+
+// Interpola entre `from` y `to`; t = 0 devuelve `from`, t = 256 devuelve `to`.
+pub fn lerp(from: u32, to: u32, t: u32) -> u32 {
+    let inv = ONE - t;
+    let rb = (((from & 0xFF00FF) * inv + (to & 0xFF00FF) * t) >> 8) & 0xFF00FF;
+    let g = (((from & 0x00FF00) * inv + (to & 0x00FF00) * t) >> 8) & 0x00FF00;
+    (from & 0xFF000000) | rb | g
+}
+
+// Convierte un valor 0.0..=1.0 al rango de punto fijo.
+pub fn to_fixed(value: f32) -> u32 {
+    (value.clamp(0.0, 1.0) * ONE as f32) as u32
+}
+
+// Multiplica un color por una luz RGB en punto fijo (256 = sin cambio), saturando en 255.
+pub fn modulate(color: u32, light: [u32; 3]) -> u32 {
+    let r = ((((color >> 16) & 0xFF) * light[0]) >> 8).min(255);
+    let g = ((((color >> 8) & 0xFF) * light[1]) >> 8).min(255);
+    let b = (((color & 0xFF) * light[2]) >> 8).min(255);
+    (color & 0xFF000000) | (r << 16) | (g << 8) | b
+}
+
+// Separa un color en sus canales RGB
+pub fn channels(color: u32) -> [u32; 3] {
+    [(color >> 16) & 0xFF, (color >> 8) & 0xFF, color & 0xFF]
+}
+
+// Lee un color escrito en hexadecimal como "FF00FF" o "#FF00FF"
+pub fn parse_hex(text: &str) -> Option<u32> {
+    let digits = text.strip_prefix('#').unwrap_or(text);
+    (digits.len() == 6).then(|| u32::from_str_radix(digits, 16).ok()).flatten()
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+use crate::raycaster::Intersect;
+use crate::texture::Texture;
+
+// Calcomanías sobre caras concretas de las paredes: sangre, zarpazos, runas y
+// flechas de señalización. Se colocan desde el nivel con
+// `:decal x y cara tipo` (cara: north, south, east, west) y se pintan sobre la
+// textura de la pared, en la franja de una unidad de altura más cercana al suelo.
+
+const SIZE: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Face {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Face {
+    pub fn parse(name: &str) -> Option<Face> {
+        match name {
+            "north" | "n" => Some(Face::North),
+            "south" | "s" => Some(Face::South),
+            "east" | "e" => Some(Face::East),
+            "west" | "w" => Some(Face::West),
+            _ => None,
+        }
+    }
+
+    // Cara de la celda golpeada por el rayo
+    pub fn of(intersect: &Intersect) -> Face {
+        match (intersect.side, intersect.angle.cos() > 0.0, intersect.angle.sin() > 0.0) {
+            (0, true, _) => Face::West,
+            (0, false, _) => Face::East,
+            (_, _, true) => Face::North,
+            _ => Face::South,
+        }
+    }
+}
+
+pub struct Decals {
+    placed: HashMap<(usize, usize, Face), String>,
+    textures: HashMap<&'static str, Texture>,
+}
+
+impl Decals {
+    pub fn from_level(directives: &[Vec<String>]) -> Self {
+        let mut placed = HashMap::new();
+        for words in directives.iter().filter(|words| words[0] == "decal") {
+            let parsed = (|| {
+                let x: usize = words.get(1)?.parse().ok()?;
+                let y: usize = words.get(2)?.parse().ok()?;
+                let face = Face::parse(words.get(3)?)?;
+                Some(((x, y, face), words.get(4)?.clone()))
+            })();
+            match parsed {
+                Some((key, kind)) => {
+                    placed.insert(key, kind);
+                }
+                None => println!("Ignoring malformed decal: :{}", words.join(" ")),
+            }
+        }
+
+        let mut textures = HashMap::new();
+        textures.insert("blood", blood());
+        textures.insert("claws", claw_marks());
+        textures.insert("rune", rune());
+        textures.insert("arrow_left", arrow(true));
+        textures.insert("arrow_right", arrow(false));
+
+        for kind in placed.values() {
+            if !textures.contains_key(kind.as_str()) {
+                println!("Unknown decal type: {}", kind);
+            }
+        }
+
+        Decals { placed, textures }
+    }
+
+    // Calcomanía de la cara que golpeó el rayo, si la hay
+    pub fn at(&self, intersect: &Intersect) -> Option<&Texture> {
+        // La celda de la pared queda justo detrás del punto de impacto
+        let x = intersect.hit_x + intersect.angle.cos() * 0.01;
+        let y = intersect.hit_y + intersect.angle.sin() * 0.01;
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let kind = self.placed.get(&(x as usize, y as usize, Face::of(intersect)))?;
+        self.textures.get(kind.as_str())
+    }
+}
+
+// Si la coordenada horizontal de la textura crece hacia la izquierda del que mira esa cara
+pub fn is_mirrored(face: Face) -> bool {
+    matches!(face, Face::East | Face::North)
+}
+
+fn paint(alpha: impl Fn(f32, f32) -> f32, color: u32) -> Texture {
+    let mut data = Vec::with_capacity(SIZE * SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let (u, v) = ((x as f32 + 0.5) / SIZE as f32, (y as f32 + 0.5) / SIZE as f32);
+            let a = (alpha(u, v).clamp(0.0, 1.0) * 255.0) as u32;
+            data.push((a << 24) | color);
+        }
+    }
+    Texture::new(SIZE, SIZE, data)
+}
+
+// Mancha irregular con gotas que escurren hacia abajo
+fn blood() -> Texture {
+    paint(
+        |u, v| {
+            let (dx, dy) = (u - 0.5, v - 0.45);
+            let angle = dy.atan2(dx);
+            let radius = 0.22 + 0.05 * (angle * 5.0).sin() + 0.03 * (angle * 11.0).cos();
+            let splat = (dx * dx + dy * dy).sqrt() < radius;
+            let drip = (u * 37.0).sin() > 0.85 && v > 0.45 && v < 0.55 + 0.35 * (u * 13.0).cos().abs();
+            if splat || drip { 0.85 } else { 0.0 }
+        },
+        0x6A0A0A,
+    )
+}
+
+// Tres arañazos paralelos en diagonal
+fn claw_marks() -> Texture {
+    paint(
+        |u, v| {
+            if !(0.2..0.8).contains(&v) {
+                return 0.0;
+            }
+            let across = u - v * 0.5;
+            let scratch = (0..3).any(|i| (across - 0.12 - i as f32 * 0.12).abs() < 0.015 * (1.0 - (v - 0.5).abs() * 2.5));
+            if scratch { 0.9 } else { 0.0 }
+        },
+        0x140A08,
+    )
+}
+
+// Círculo con una estrella de cinco puntas trazada en su interior
+fn rune() -> Texture {
+    paint(
+        |u, v| {
+            let (dx, dy) = (u - 0.5, v - 0.5);
+            let distance = (dx * dx + dy * dy).sqrt();
+            let ring = (distance - 0.3).abs() < 0.02;
+            // Las cinco líneas de la estrella unen vértices alternos del pentágono
+            let vertex = |i: usize| {
+                let angle = i as f32 / 5.0 * TAU - TAU / 4.0;
+                (0.5 + 0.3 * angle.cos(), 0.5 + 0.3 * angle.sin())
+            };
+            let star = (0..5).any(|i| {
+                let ((x0, y0), (x1, y1)) = (vertex(i), vertex((i + 2) % 5));
+                let (ex, ey) = (x1 - x0, y1 - y0);
+                let t = (((u - x0) * ex + (v - y0) * ey) / (ex * ex + ey * ey)).clamp(0.0, 1.0);
+                ((u - x0 - ex * t).powi(2) + (v - y0 - ey * t).powi(2)).sqrt() < 0.015
+            });
+            if ring || star { 1.0 } else { 0.0 }
+        },
+        0xFF5A1E,
+    )
+}
+
+// Flecha pintada sobre un cartel de madera
+fn arrow(left: bool) -> Texture {
+    let board = paint(|u, v| if (0.1..0.9).contains(&u) && (0.3..0.7).contains(&v) { 1.0 } else { 0.0 }, 0x5A3A1C);
+    let mut data = board.data;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let u = (x as f32 + 0.5) / SIZE as f32;
+            let u = if left { 1.0 - u } else { u };
+            let v = (y as f32 + 0.5) / SIZE as f32;
+            let shaft = (0.2..0.6).contains(&u) && (v - 0.5).abs() < 0.05;
+            let head = (0.6..0.82).contains(&u) && (v - 0.5).abs() < (0.82 - u) * 0.8;
+            if shaft || head {
+                data[y * SIZE + x] = 0xFFE8DCC0;
+            }
+        }
+    }
+    Texture::new(SIZE, SIZE, data)
+}
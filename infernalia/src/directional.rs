@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::assets::Assets;
+use crate::color;
+use crate::texture::Texture;
+
+// Sprites con estados de animación (idle, walk, attack, die...) y ocho direcciones.
+// La dirección que se dibuja depende del ángulo entre quien mira y hacia dónde
+// mira la entidad: la dirección 0 es de frente al espectador y cada una de las
+// siguientes gira 45° más en sentido horario visto desde arriba.
+//
+// Una hoja de sprites se describe con un archivo de texto como este:
+//
+//     sheet demonio.png 64 64 FF00FF
+//     state idle 2 0.5 8
+//     state walk 4 0.15 8
+//     state attack 3 0.12 8
+//     state die 5 0.15 1 once
+//
+// `sheet` da la imagen (relativa al archivo), el tamaño de cada fotograma y, si la imagen
+// no tiene canal alfa, el color que se vuelve transparente. Cada
+// `state` da su nombre, fotogramas, segundos por fotograma y filas de direcciones
+// (8, o 1 si se ve igual desde todos lados); `once` hace que no se repita y se quede
+// en el último fotograma. Los estados ocupan filas consecutivas de la imagen, una
+// fila por dirección y un fotograma por columna.
+
+pub const DIRECTIONS: usize = 8;
+
+pub struct SheetState {
+    directions: Vec<Vec<Texture>>, // directions[dirección][fotograma]
+    frame_time: f32,
+    looping: bool,
+}
+
+pub struct SpriteSheet {
+    states: HashMap<String, SheetState>,
+}
+
+impl SpriteSheet {
+    // `description` es el nombre del recurso, p. ej. "sprites/demon.sheet"
+    pub fn load(assets: &mut Assets, description: &str) -> Result<SpriteSheet, String> {
+        let text = assets.read_text(description).ok_or_else(|| format!("Failed to read sprite sheet {}", description))?;
+        let folder = Path::new(description).parent().unwrap_or(Path::new(""));
+
+        let mut image: Option<(Texture, usize, usize)> = None;
+        let mut row = 0;
+        let mut states = HashMap::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let error = || format!("{}:{}: invalid line '{}'", description, number + 1, line);
+            match words.as_slice() {
+                [] => {}
+                [comment, ..] if comment.starts_with('#') => {}
+                ["sheet", file, width, height, key @ ..] => {
+                    let mut texture = Texture::clone(&assets.texture(&folder.join(file).to_string_lossy()));
+                    match key {
+                        [] => {}
+                        [key] => texture = texture.with_color_key(color::parse_hex(key).ok_or_else(error)?),
+                        _ => return Err(error()),
+                    }
+                    image = Some((texture, width.parse().map_err(|_| error())?, height.parse().map_err(|_| error())?));
+                }
+                ["state", name, frames, frame_time, directions, rest @ ..] => {
+                    let (texture, width, height) = image.as_ref().ok_or_else(|| format!("{}: 'state' before 'sheet'", description))?;
+                    let frames: usize = frames.parse().map_err(|_| error())?;
+                    let directions: usize = directions.parse().map_err(|_| error())?;
+                    if directions != 1 && directions != DIRECTIONS {
+                        return Err(format!("{}: state '{}' needs 1 or {} directions", description, name, DIRECTIONS));
+                    }
+
+                    let rows = (row..row + directions)
+                        .map(|r| (0..frames).map(|column| texture.crop(column * width, r * height, *width, *height)).collect())
+                        .collect::<Result<Vec<Vec<Texture>>, String>>()?;
+                    row += directions;
+
+                    states.insert(name.to_string(), SheetState {
+                        directions: rows,
+                        frame_time: frame_time.parse().map_err(|_| error())?,
+                        looping: rest != ["once"],
+                    });
+                }
+                _ => return Err(error()),
+            }
+        }
+
+        Ok(SpriteSheet { states })
+    }
+
+    pub fn from_states(states: HashMap<String, SheetState>) -> SpriteSheet {
+        SpriteSheet { states }
+    }
+
+    pub fn has_state(&self, name: &str) -> bool {
+        self.states.contains_key(name)
+    }
+}
+
+impl SheetState {
+    pub fn new(directions: Vec<Vec<Texture>>, frame_time: f32, looping: bool) -> Self {
+        SheetState { directions, frame_time, looping }
+    }
+}
+
+// Estado de animación de una entidad que usa una hoja compartida
+pub struct DirectionalSprite {
+    sheet: Rc<SpriteSheet>,
+    state: String,
+    time: f32,
+}
+
+impl DirectionalSprite {
+    pub fn new(sheet: Rc<SpriteSheet>, state: &str) -> Self {
+        DirectionalSprite { sheet, state: state.to_string(), time: 0.0 }
+    }
+
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    // Cambia de estado y empieza su animación desde el principio (si no estaba ya en él)
+    pub fn set_state(&mut self, state: &str) {
+        if self.state != state && self.sheet.has_state(state) {
+            self.state = state.to_string();
+            self.time = 0.0;
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.time += delta_time;
+    }
+
+    // Si una animación que no se repite ya llegó a su último fotograma
+    pub fn finished(&self) -> bool {
+        self.sheet.states.get(&self.state).is_some_and(|state| {
+            !state.looping && self.time >= state.frame_time * state.directions[0].len() as f32
+        })
+    }
+
+    // Fotograma visto desde (viewer_x, viewer_y) para una entidad en (x, y) que mira hacia `facing`
+    pub fn frame(&self, x: f32, y: f32, facing: f32, viewer_x: f32, viewer_y: f32) -> &Texture {
+        let state = &self.sheet.states[&self.state];
+
+        let to_viewer = (viewer_y - y).atan2(viewer_x - x);
+        let step = TAU / DIRECTIONS as f32;
+        let direction = (((to_viewer - facing) / step).round() as isize).rem_euclid(DIRECTIONS as isize) as usize;
+        let frames = &state.directions[direction.min(state.directions.len() - 1)];
+
+        let index = (self.time / state.frame_time) as usize;
+        let index = if state.looping { index % frames.len() } else { index.min(frames.len() - 1) };
+        &frames[index]
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use crate::map::{is_door, is_wall, key_for_door};
+use crate::player::Player;
+use crate::texture::Texture;
+
+// Puertas correderas: una hoja delgada en el centro de la celda que se desliza
+// hacia un lado al usarla, se queda abierta un rato y vuelve a cerrarse.
+
+const SLIDE_SPEED: f32 = 1.5;  // Fracción de la hoja que se desliza por segundo
+const STAY_OPEN: f32 = 3.0;    // Segundos que la puerta permanece abierta
+const USE_DISTANCE: f32 = 1.5; // Alcance del jugador para abrir una puerta
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DoorState {
+    Closed,
+    Opening,
+    Open,
+    Closing,
+}
+
+pub struct Door {
+    pub state: DoorState,
+    pub offset: f32,      // 0.0 cerrada, 1.0 completamente abierta
+    pub horizontal: bool, // La hoja va de este a oeste (se cruza de norte a sur)
+    pub lock: Option<char>, // Llave necesaria para abrirla
+    timer: f32,
+}
+
+impl Door {
+    // Dónde cruza el rayo la hoja de la puerta de la celda (x, y), si la cruza:
+    // devuelve la distancia y la posición a lo largo de la hoja (0.0..1.0).
+    pub fn intersect(&self, x: usize, y: usize, origin_x: f32, origin_y: f32, cos_angle: f32, sin_angle: f32) -> Option<(f32, f32)> {
+        let (plane, origin, direction, along_origin, along_direction, cell) = if self.horizontal {
+            (y as f32 + 0.5, origin_y, sin_angle, origin_x, cos_angle, x as f32)
+        } else {
+            (x as f32 + 0.5, origin_x, cos_angle, origin_y, sin_angle, y as f32)
+        };
+
+        if direction == 0.0 {
+            return None;
+        }
+
+        let distance = (plane - origin) / direction;
+        let along = along_origin + along_direction * distance - cell;
+        if distance <= 0.0 || !(0.0..1.0).contains(&along) || along < self.offset {
+            return None; // Pasa por el hueco abierto o sale de la celda por un lado
+        }
+
+        Some((distance, along))
+    }
+}
+
+pub struct Doors {
+    doors: HashMap<(usize, usize), Door>,
+}
+
+impl Doors {
+    pub fn from_maze(maze: &[Vec<char>]) -> Self {
+        let mut doors = HashMap::new();
+        for (y, row) in maze.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if !is_door(cell) {
+                    continue;
+                }
+
+                // Si hay pared a los lados este y oeste, la hoja cierra el paso norte-sur
+                let west = x > 0 && is_wall(row[x - 1]);
+                let east = row.get(x + 1).is_some_and(|&c| is_wall(c));
+                doors.insert((x, y), Door {
+                    state: DoorState::Closed,
+                    offset: 0.0,
+                    horizontal: west && east,
+                    lock: key_for_door(cell),
+                    timer: 0.0,
+                });
+            }
+        }
+
+        Doors { doors }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&Door> {
+        self.doors.get(&(x, y))
+    }
+
+    // Una puerta solo deja pasar al jugador cuando está completamente abierta
+    pub fn is_blocking(&self, x: usize, y: usize) -> bool {
+        self.get(x, y).is_some_and(|door| door.state != DoorState::Open)
+    }
+
+    // Abre la primera puerta que el jugador tenga delante, si está al alcance
+    // y, cuando está cerrada con llave, si el jugador lleva la llave
+    pub fn try_open(&mut self, player: &Player) -> bool {
+        for (x, y) in player.facing_cells(USE_DISTANCE) {
+            if let Some(door) = self.doors.get_mut(&(x, y)) {
+                if let Some(key) = door.lock {
+                    if !player.keys.contains(&key) {
+                        println!("La puerta está cerrada con llave: necesitas la llave '{}'.", key);
+                        return false;
+                    }
+                }
+                if matches!(door.state, DoorState::Closed | DoorState::Closing) {
+                    door.state = DoorState::Opening;
+                    return true;
+                }
+                return false;
+            }
+        }
+        false
+    }
+
+    pub fn update(&mut self, delta_time: f32, player: &Player) {
+        for (&(x, y), door) in self.doors.iter_mut() {
+            match door.state {
+                DoorState::Closed => {}
+                DoorState::Opening => {
+                    door.offset += SLIDE_SPEED * delta_time;
+                    if door.offset >= 1.0 {
+                        door.offset = 1.0;
+                        door.state = DoorState::Open;
+                        door.timer = STAY_OPEN;
+                    }
+                }
+                DoorState::Open => {
+                    // No se cierra mientras el jugador (con su radio de colisión) esté debajo
+                    let reach = 0.5 + player.collision_radius;
+                    if (player.pos.x - (x as f32 + 0.5)).abs() < reach && (player.pos.y - (y as f32 + 0.5)).abs() < reach {
+                        door.timer = STAY_OPEN;
+                    }
+                    door.timer -= delta_time;
+                    if door.timer <= 0.0 {
+                        door.state = DoorState::Closing;
+                    }
+                }
+                DoorState::Closing => {
+                    door.offset -= SLIDE_SPEED * delta_time;
+                    if door.offset <= 0.0 {
+                        door.offset = 0.0;
+                        door.state = DoorState::Closed;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Tablones de madera con bandas de metal del color indicado
+pub fn door_texture(band_color: u32) -> Texture {
+    let size = 64;
+    let mut data = Vec::with_capacity(size * size);
+    for y in 0..size {
+        for x in 0..size {
+            let color = if (10..14).contains(&y) || (50..54).contains(&y) {
+                0xFF000000 | band_color
+            } else if x % 16 == 0 {
+                0xFF2A1608 // Junta entre tablones
+            } else {
+                // Veta de la madera
+                let grain = ((x as f32 * 0.7 + (y as f32 * 0.15).sin() * 3.0).sin() * 12.0) as i32;
+                let r = (110 + grain) as u32;
+                let g = (62 + grain / 2) as u32;
+                0xFF000000 | (r << 16) | (g << 8) | 24
+            };
+            data.push(color);
+        }
+    }
+    Texture::new(size, size, data)
+}
@@ -0,0 +1,75 @@
+use crate::color;
+
+// Niebla por distancia. El factor se calcula una vez por columna (paredes) o por
+// fila (suelo) y luego se mezcla por píxel con aritmética entera.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FogMode {
+    Off,
+    Linear { start: f32, end: f32 },
+    Exponential { density: f32 },
+}
+
+pub struct Fog {
+    pub mode: FogMode,
+    pub color: u32,
+    pub side_shade: u32, // Oscurecimiento de las caras horizontales (0..=256)
+    density: f32,        // La del modo exponencial, para volver a ella al cambiar de modo
+}
+
+impl Fog {
+    pub fn new(mode: FogMode, color: u32) -> Self {
+        Fog {
+            mode,
+            color,
+            side_shade: 180,
+            density: match mode {
+                FogMode::Exponential { density } => density,
+                _ => 0.18,
+            },
+        }
+    }
+
+    // Neblina rojiza del infierno
+    pub fn hellish() -> Self {
+        Fog::new(FogMode::Exponential { density: 0.18 }, 0x3A0A06)
+    }
+
+    // Cantidad de niebla a cierta distancia, en punto fijo (0 = nada, 256 = total)
+    pub fn factor(&self, distance: f32) -> u32 {
+        let amount = match self.mode {
+            FogMode::Off => 0.0,
+            FogMode::Linear { start, end } => {
+                if end <= start {
+                    if distance >= end { 1.0 } else { 0.0 }
+                } else {
+                    (distance - start) / (end - start)
+                }
+            }
+            FogMode::Exponential { density } => 1.0 - (-density * distance).exp(),
+        };
+        color::to_fixed(amount)
+    }
+
+    pub fn apply(&self, color: u32, factor: u32) -> u32 {
+        if factor == 0 {
+            return color;
+        }
+        color::lerp(color, self.color, factor)
+    }
+
+    // La misma niebla con su color apagado por la luz `light` (0..=256): en el modo
+    // oscuridad lo lejano se funde hacia el negro en lugar de hacia la neblina rojiza
+    pub fn dimmed(&self, light: u32) -> Fog {
+        Fog { color: color::scale(self.color, light), ..*self }
+    }
+
+    // Alterna entre apagada, lineal y exponencial
+    pub fn cycle_mode(&mut self) {
+        self.mode = match self.mode {
+            FogMode::Off => FogMode::Linear { start: 1.0, end: 12.0 },
+            FogMode::Linear { .. } => FogMode::Exponential { density: self.density },
+            FogMode::Exponential { .. } => FogMode::Off,
+        };
+    }
+}
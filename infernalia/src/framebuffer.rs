@@ -30,6 +30,15 @@ impl Framebuffer {
         self.buffer.fill(self.background_color);
     }
 
+    // Oscurece lo ya dibujado, p. ej. la partida congelada bajo un menú (0 negro, 1 igual)
+    pub fn dim(&mut self, brightness: f32) {
+        for pixel in self.buffer.iter_mut() {
+            let color = *pixel;
+            let channel = |shift: u32| ((((color >> shift) & 0xFF) as f32 * brightness) as u32) << shift;
+            *pixel = channel(16) | channel(8) | channel(0);
+        }
+    }
+
     pub fn point(&mut self, x: usize, y: usize) {
         if x < self.width && y < self.height {
             self.buffer[y * self.width + x] = self.current_color;
@@ -73,34 +82,3 @@ impl Framebuffer {
         }
     }
 }
-pub struct Texture {
-    pub data: Vec<u32>, // Datos de la textura en formato RGB
-    pub width: usize,   // Ancho de la textura
-    pub height: usize,  // Alto de la textura
-}
-
-impl Texture {
-    pub fn load_from_file(file_path: &str) -> Result<Texture, String> {
-        let img = image::open(file_path).map_err(|e| e.to_string())?;
-        let img = img.to_rgb8();
-        let (width, height) = img.dimensions();
-        let data = img.into_raw();
-
-        // Convierte el formato de imagen a un vector de u32 en formato RGBA
-        let mut texture_data = Vec::with_capacity((width * height) as usize);
-        for pixel in data.chunks_exact(3) {
-            let r = pixel[0] as u32;
-            let g = pixel[1] as u32;
-            let b = pixel[2] as u32;
-            let a = 255;
-            let rgba = (a << 24) | (r << 16) | (g << 8) | b;
-            texture_data.push(rgba);
-        }
-
-        Ok(Texture {
-            data: texture_data,
-            width: width as usize,
-            height: height as usize,
-        })
-    }
-}
@@ -0,0 +1,171 @@
+use std::f32::consts::PI;
+use std::rc::Rc;
+
+use rusttype::Font;
+
+use crate::assets::Assets;
+use crate::atlas::Atlas;
+use crate::campaign::Campaign;
+use crate::directional::SpriteSheet;
+use crate::framebuffer::Framebuffer;
+use crate::level::Level;
+use crate::monster::demon_sheet;
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::sfx::{Sound, SoundManager};
+
+// La partida en curso y todo lo que comparten los estados del juego (ver `state.rs`):
+// recursos, sonidos, la campaña, el nivel actual, el jugador y los tiempos. Cada estado
+// decide qué hace con ello en cada fotograma.
+
+const SUCCESS_RADIUS: f32 = 1.5; // Radio de éxito aumentado
+const INITIAL_ANGLE: f32 = PI / 3.0;
+const INITIAL_FOV: f32 = PI / 3.0;
+
+// Antorchas, braseros y lava del laberinto sobre una luz ambiental muy tenue
+const AMBIENT_LIGHT: f32 = 0.2;
+const DARKNESS_AMBIENT_LIGHT: f32 = 0.02; // Modo oscuridad: solo el farol y las antorchas
+
+// El nombre del círculo se muestra unos segundos al entrar en él
+const BANNER_DURATION: f32 = 4.0;
+
+pub enum ViewMode {
+    View2D,
+    View3D,
+}
+
+pub struct Game {
+    pub assets: Assets,
+    pub settings: Settings,
+    pub sound_manager: SoundManager,
+    pub font: Option<Rc<Font<'static>>>,
+    pub footsteps: Sound,
+    pub campaign: Campaign,
+    pub atlas: Atlas,
+    demons: Rc<SpriteSheet>,
+    maze_filename: String,
+    pub level: Level,
+    pub player: Player,
+    pub view_mode: ViewMode,
+    pub level_time: f32, // Tiempo del círculo actual y de toda la partida, solo mientras se juega
+    pub total_time: f32,
+    pub clock: f32,      // Segundos jugados, para el parpadeo de las luces (se para en las pausas)
+    pub banner_time: f32,
+    pub last_mouse: (f32, f32),
+    pub mouse_was_down: bool, // Para que mantener pulsado el botón no cuente como varios clics
+    pub fps: usize,
+    circle_start: (usize, f32), // Tesoros y aceite al entrar en el círculo, para reiniciarlo
+}
+
+impl Game {
+    pub fn new(mut assets: Assets, settings: Settings) -> Result<Game, String> {
+        // Load the font
+        let font = assets.font("fonts/AGaramondPro-Regular.otf");
+
+        // Initialize SoundManager and play ambient sound
+        let sound_manager = SoundManager::new();
+        let footsteps = assets.sound("music/steps.mp3");
+
+        // Los nueve círculos, cada uno con su aspecto, su música y su laberinto
+        let campaign = Campaign::load(&assets);
+        let maze_filename = assets.path("generated_maze.txt").to_string_lossy().into_owned();
+        let demons = demon_sheet(&mut assets);
+
+        // Texturas: las imágenes sueltas de la carpeta (del tema, del disco o incluidas en el
+        // ejecutable) y, si hay, las regiones de su atlas. Cada círculo elige las suyas y
+        // genera las que falten (o todas, con `procedural_textures`).
+        let atlas = Atlas::from_assets(&mut assets, "textures");
+
+        let level = Level::new(0, &campaign.circles[0], &atlas, &settings, &demons, &maze_filename, AMBIENT_LIGHT)?;
+        sound_manager.play_ambient(&campaign.circles[0].ambient_track(&mut assets));
+        println!("Start position found at: x = {}, y = {}", level.start.0, level.start.1);
+
+        let player = Player::new(level.start.0, level.start.1, INITIAL_ANGLE, INITIAL_FOV, 0.01);
+        println!("Initial player position: x = {}, y = {}", player.pos.x, player.pos.y);
+        let circle_start = (player.treasures, player.lantern.fuel);
+
+        Ok(Game {
+            assets,
+            settings,
+            sound_manager,
+            font,
+            footsteps,
+            campaign,
+            atlas,
+            demons,
+            maze_filename,
+            level,
+            player,
+            view_mode: ViewMode::View3D,
+            level_time: 0.0,
+            total_time: 0.0,
+            clock: 0.0,
+            banner_time: BANNER_DURATION,
+            last_mouse: (0.0, 0.0),
+            mouse_was_down: false,
+            fps: 0,
+            circle_start,
+        })
+    }
+
+    // Baja al círculo `number` con un laberinto nuevo y deja al jugador en su inicio
+    pub fn enter_circle(&mut self, number: usize) -> Result<(), String> {
+        let circle = self.campaign.circles.get(number).ok_or_else(|| format!("There is no circle {}", number + 1))?;
+        self.level = Level::new(number, circle, &self.atlas, &self.settings, &self.demons, &self.maze_filename, AMBIENT_LIGHT)?;
+        self.sound_manager.stop_ambient();
+        self.sound_manager.play_ambient(&circle.ambient_track(&mut self.assets));
+        self.place_player();
+        Ok(())
+    }
+
+    // Empieza otra vez el círculo actual, en el mismo laberinto o en uno nuevo. Lo
+    // recogido en el intento se pierde, pero su tiempo cuenta para el total.
+    pub fn restart_circle(&mut self, new_maze: bool) -> Result<(), String> {
+        self.total_time += self.level_time;
+        (self.player.treasures, self.player.lantern.fuel) = self.circle_start;
+        if new_maze {
+            self.enter_circle(self.level.number)
+        } else {
+            self.level.restart(&self.atlas, &self.demons, &self.maze_filename, AMBIENT_LIGHT)?;
+            self.place_player();
+            Ok(())
+        }
+    }
+
+    // Deja al jugador en el inicio del nivel, sin llaves y con el cronómetro a cero
+    fn place_player(&mut self) {
+        if self.player.lantern.lit {
+            self.level.lighting.set_ambient(DARKNESS_AMBIENT_LIGHT);
+        }
+        (self.player.pos.x, self.player.pos.y) = self.level.start;
+        self.player.a = INITIAL_ANGLE;
+        self.player.keys.clear();
+        self.banner_time = BANNER_DURATION;
+        self.level_time = 0.0;
+        self.circle_start = (self.player.treasures, self.player.lantern.fuel);
+    }
+
+    pub fn is_last_circle(&self) -> bool {
+        self.level.number + 1 >= self.campaign.circles.len()
+    }
+
+    // Check if the player is within the success radius of the goal
+    pub fn reached_goal(&self) -> bool {
+        let (goal_x, goal_y) = self.level.goal;
+        let distance_to_goal = ((self.player.pos.x - goal_x as f32 + 0.5).powi(2) + (self.player.pos.y - goal_y as f32 + 0.5).powi(2)).sqrt();
+        distance_to_goal <= SUCCESS_RADIUS
+    }
+
+    // Alternar el modo oscuridad con el farol del jugador
+    pub fn toggle_lantern(&mut self) {
+        self.player.lantern.lit = !self.player.lantern.lit;
+        self.level.lighting.set_ambient(if self.player.lantern.lit { DARKNESS_AMBIENT_LIGHT } else { AMBIENT_LIGHT });
+    }
+
+    // Texto con la fuente TrueType; si no se pudo cargar, no se dibuja
+    pub fn draw_text(&self, framebuffer: &mut Framebuffer, x: usize, y: usize, text: &str, scale: f32) {
+        if let Some(font) = &self.font {
+            framebuffer.draw_text(x, y, text, font, scale);
+        }
+    }
+}
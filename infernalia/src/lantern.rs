@@ -0,0 +1,77 @@
+use crate::color;
+
+// Farol que lleva el jugador en el modo oscuridad. Ilumina un cono hacia donde
+// mira y un pequeño radio a su alrededor, y su alcance se reduce al gastarse el aceite.
+
+pub struct Lantern {
+    pub lit: bool,        // Solo se enciende en el modo oscuridad
+    pub fuel: f32,        // Segundos de aceite restantes
+    pub max_fuel: f32,
+    pub drain_rate: f32,  // Aceite gastado por segundo
+    pub radius: f32,      // Alcance con el depósito lleno
+    pub cone: f32,        // Semiángulo del cono de luz (radianes)
+    pub color: u32,
+}
+
+impl Lantern {
+    pub fn new() -> Self {
+        Lantern {
+            lit: false,
+            fuel: 90.0,
+            max_fuel: 90.0,
+            drain_rate: 1.0,
+            radius: 7.0,
+            cone: 0.35,
+            color: 0xFFD9A0,
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if self.lit {
+            self.fuel = (self.fuel - self.drain_rate * delta_time).max(0.0);
+        }
+    }
+
+    pub fn refill(&mut self, amount: f32) {
+        self.fuel = (self.fuel + amount).min(self.max_fuel);
+    }
+
+    pub fn fuel_ratio(&self) -> f32 {
+        self.fuel / self.max_fuel
+    }
+
+    // Alcance actual: se apaga poco a poco y sin aceite solo queda una brasa
+    pub fn reach(&self) -> f32 {
+        0.8 + (self.radius - 0.8) * self.fuel_ratio().sqrt()
+    }
+
+    // Cuánto del cono llega a una dirección separada `offset` radianes de la vista
+    pub fn cone_factor(&self, offset: f32) -> f32 {
+        let offset = offset.abs();
+        if offset <= self.cone {
+            1.0
+        } else {
+            (1.0 - (offset - self.cone) / self.cone).max(0.0)
+        }
+    }
+
+    // Suma la luz del farol a `light` para un punto a `distance` del jugador
+    pub fn add_light(&self, light: &mut [u32; 3], distance: f32, cone_factor: f32) {
+        if !self.lit {
+            return;
+        }
+
+        // Fuera del cono el farol solo alumbra un radio corto alrededor del jugador
+        let reach = self.reach();
+        let halo = (1.0 - distance / 1.2).max(0.0);
+        let beam = (1.0 - distance / reach).max(0.0) * cone_factor;
+        let strength = color::to_fixed(halo.max(beam).powi(2));
+        if strength == 0 {
+            return;
+        }
+
+        for (light, channel) in light.iter_mut().zip(color::channels(self.color)) {
+            *light += (channel * strength) >> 8;
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::rc::Rc;
+
+use crate::animation::AnimatedTexture;
+use crate::atlas::Atlas;
+use crate::campaign::{Circle, Surface};
+use crate::directional::SpriteSheet;
+use crate::fog::Fog;
+use crate::light::LightMap;
+use crate::map::{is_open, load_level};
+use crate::maze::{generate_and_save_maze, MazeStyle};
+use crate::monster::{monsters_from_maze, Monster};
+use crate::procedural;
+use crate::props::sprites_from_maze;
+use crate::settings::Settings;
+use crate::sprite::Sprite;
+use crate::texture::Texture;
+use crate::wall_textures::WallTextures;
+use crate::world::World;
+
+// Todo lo que pertenece a un nivel de la campaña: el laberinto generado para su círculo,
+// lo que hay en él y su aspecto. Al pasar al siguiente círculo se crea uno nuevo.
+pub struct Level {
+    pub number: usize, // Índice del círculo en la campaña, desde 0
+    pub maze: Vec<Vec<char>>,
+    pub start: (f32, f32),
+    pub goal: (usize, usize),
+    pub world: World,
+    pub sprites: Vec<Sprite>,
+    pub monsters: Vec<Monster>,
+    pub lighting: LightMap,
+    pub wall_texture: Texture,
+    pub floor_texture: Texture,
+    pub sky: AnimatedTexture,
+    pub wall_textures: WallTextures,
+    pub fog: Fog,
+}
+
+// Cada nivel es más grande que el anterior, con más bucles, más charcos y más demonios,
+// y desde el cuarto con pasillos más estrechos
+pub struct Difficulty {
+    pub width: usize,  // Tamaño que se pasa a `generate_and_save_maze`
+    pub height: usize,
+    pub path_width: usize,
+    pub braid: f32,
+    pub danger: f32,
+}
+
+impl Difficulty {
+    pub fn for_level(number: usize) -> Self {
+        Difficulty {
+            width: 10 + number * 2,
+            height: 8 + number * 2,
+            // Las salas secretas y los muros finos necesitan pasillos de 4 o más
+            path_width: if number < 3 { 5 } else { 4 },
+            braid: (number as f32 * 0.08).min(0.6),
+            danger: number as f32 * 0.15,
+        }
+    }
+}
+
+impl Level {
+    pub fn new(
+        number: usize,
+        circle: &Circle,
+        atlas: &Atlas,
+        settings: &Settings,
+        demons: &Rc<SpriteSheet>,
+        maze_filename: &str,
+        ambient_light: f32,
+    ) -> Result<Level, String> {
+        println!("Circle {}: {} ({}).", number + 1, circle.name, circle.subtitle);
+
+        // Generar y guardar el mapa proceduralmente, del tamaño y la dificultad del nivel
+        let difficulty = Difficulty::for_level(number);
+        let style = MazeStyle { braid: difficulty.braid, danger: difficulty.danger, ..circle.maze };
+        generate_and_save_maze(difficulty.width, difficulty.height, maze_filename, difficulty.path_width, &style);
+        let layout = Layout::load(maze_filename, atlas, demons, ambient_light)?;
+
+        // Una semilla nueva en cada nivel, así cada laberinto tiene su propio aspecto
+        let seed: u32 = rand::random();
+        let surface = |surface: &Surface, width: usize, height: usize| {
+            let image = surface.image.as_deref().and_then(|name| atlas.texture(name)).filter(|_| !settings.procedural_textures);
+            let texture = match image {
+                Some(texture) => texture.clone(),
+                None => {
+                    println!("Generating '{}' texture (seed {}).", surface.generate, seed);
+                    procedural::by_name(&surface.generate, width, height, seed).unwrap_or_else(|| {
+                        println!("Unknown texture generator '{}'.", surface.generate);
+                        procedural::wall(width, seed)
+                    })
+                }
+            };
+            match circle.tint() {
+                Some(tint) => texture.tinted(tint),
+                None => texture,
+            }
+        };
+        let wall_texture = surface(&circle.walls, 128, 128);
+        let floor_texture = surface(&circle.floor, 128, 128);
+        let sky = AnimatedTexture::scrolling(surface(&circle.sky, 256, 128), 0.01, 0.0); // Nubes a la deriva
+        let wall_textures = WallTextures::new(&wall_texture, atlas, seed);
+
+        Ok(Level {
+            number,
+            maze: layout.maze,
+            start: layout.start,
+            goal: layout.goal,
+            world: layout.world,
+            sprites: layout.sprites,
+            monsters: layout.monsters,
+            lighting: layout.lighting,
+            wall_texture,
+            floor_texture,
+            sky,
+            wall_textures,
+            fog: circle.fog(),
+        })
+    }
+
+    // Vuelve a leer el laberinto guardado: puertas cerradas, objetos y demonios en su sitio.
+    // Las texturas y la niebla del círculo no cambian.
+    pub fn restart(&mut self, atlas: &Atlas, demons: &Rc<SpriteSheet>, maze_filename: &str, ambient_light: f32) -> Result<(), String> {
+        let layout = Layout::load(maze_filename, atlas, demons, ambient_light)?;
+        self.maze = layout.maze;
+        self.start = layout.start;
+        self.goal = layout.goal;
+        self.world = layout.world;
+        self.sprites = layout.sprites;
+        self.monsters = layout.monsters;
+        self.lighting = layout.lighting;
+        Ok(())
+    }
+}
+
+// Lo que se lee del archivo del laberinto, tanto al crear el nivel como al reiniciarlo
+struct Layout {
+    maze: Vec<Vec<char>>,
+    start: (f32, f32),
+    goal: (usize, usize),
+    world: World,
+    sprites: Vec<Sprite>,
+    monsters: Vec<Monster>,
+    lighting: LightMap,
+}
+
+impl Layout {
+    fn load(maze_filename: &str, atlas: &Atlas, demons: &Rc<SpriteSheet>, ambient_light: f32) -> Result<Layout, String> {
+        let (mut maze, directives) = load_level(maze_filename);
+        // Los demonios salen de sus celdas 'd', que quedan como suelo
+        let monsters = monsters_from_maze(&mut maze, demons);
+        println!("{} demons spawned.", monsters.len());
+        let (start_x, start_y) = find_start_position(&maze).ok_or("Start position 'p' not found in the maze")?;
+        let goal = find_cell(&maze, 'g').ok_or("Goal 'g' not found in the maze")?;
+
+        // Antorchas, braseros y lava del laberinto sobre la luz ambiental
+        let lighting = LightMap::from_maze(&maze, ambient_light);
+        println!("{} light sources placed.", lighting.lights.len());
+
+        Ok(Layout {
+            start: (start_x as f32 + 0.5, start_y as f32 + 0.5),
+            goal,
+            world: World::from_level(&maze, &directives),
+            sprites: sprites_from_maze(&maze, atlas),
+            maze,
+            monsters,
+            lighting,
+        })
+    }
+}
+
+// La 'p' del mapa es un pilar en la esquina de la celda de inicio: se empieza a su lado.
+// Sin ella, en la primera celda libre (que podría estar en una sala secreta).
+fn find_start_position(maze: &[Vec<char>]) -> Option<(usize, usize)> {
+    for (row_index, row) in maze.iter().enumerate() {
+        for (col_index, &cell) in row.iter().enumerate() {
+            if cell != 'p' {
+                continue;
+            }
+            let beside = [(col_index + 1, row_index), (col_index, row_index + 1)];
+            if let Some(&start) = beside.iter().find(|&&(x, y)| maze.get(y).and_then(|row| row.get(x)).is_some_and(|&c| is_open(c))) {
+                return Some(start);
+            }
+        }
+    }
+    find_cell(maze, ' ')
+}
+
+fn find_cell(maze: &[Vec<char>], wanted: char) -> Option<(usize, usize)> {
+    for (row_index, row) in maze.iter().enumerate() {
+        for (col_index, &cell) in row.iter().enumerate() {
+            if cell == wanted {
+                return Some((col_index, row_index));
+            }
+        }
+    }
+    None
+}
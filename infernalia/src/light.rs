@@ -0,0 +1,194 @@
+use crate::color;
+use crate::map::{is_door, is_partial_wall, is_see_through, is_wall};
+
+// Fuentes de luz colocadas en el nivel. Cada celda guarda qué luces la ven
+// (la oclusión contra la cuadrícula se calcula una sola vez al cargar) y en
+// cada cuadro solo se actualiza el parpadeo de cada luz.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LightKind {
+    Torch,
+    Brazier,
+    LavaPool,
+}
+
+impl LightKind {
+    pub fn from_cell(cell: char) -> Option<LightKind> {
+        match cell {
+            't' => Some(LightKind::Torch),
+            'u' => Some(LightKind::Brazier),
+            '~' => Some(LightKind::LavaPool),
+            _ => None,
+        }
+    }
+}
+
+pub struct Light {
+    pub x: f32,
+    pub y: f32,
+    pub kind: LightKind,
+    pub color: u32,
+    pub radius: f32,    // Distancia a la que la luz se apaga por completo
+    pub intensity: f32,
+    phase: f32,         // Desfase para que las antorchas no parpadeen al unísono
+}
+
+impl Light {
+    pub fn new(kind: LightKind, x: f32, y: f32) -> Self {
+        let (color, radius, intensity) = match kind {
+            LightKind::Torch => (0xFF9933, 4.5, 1.2),
+            LightKind::Brazier => (0xFF6A1A, 6.0, 1.5),
+            LightKind::LavaPool => (0xFF3300, 3.0, 1.0),
+        };
+
+        Light {
+            x,
+            y,
+            kind,
+            color,
+            radius,
+            intensity,
+            phase: (x * 12.9898 + y * 78.233).sin().abs() * 10.0,
+        }
+    }
+
+    // Factor de parpadeo en el instante `time` (segundos)
+    pub fn flicker(&self, time: f32) -> f32 {
+        let t = time + self.phase;
+        match self.kind {
+            LightKind::Torch => 0.85 + 0.10 * (t * 9.0).sin() + 0.05 * (t * 23.0).sin(),
+            LightKind::Brazier => 0.90 + 0.07 * (t * 5.0).sin() + 0.03 * (t * 17.0).sin(),
+            LightKind::LavaPool => 0.80 + 0.20 * (t * 1.5).sin(),
+        }
+    }
+}
+
+pub struct LightMap {
+    pub lights: Vec<Light>,
+    pub ambient: u32,        // Luz ambiental en punto fijo (256 = brillo completo)
+    width: usize,
+    visible: Vec<Vec<usize>>, // Índices de las luces visibles desde cada celda
+    current: Vec<[u32; 3]>,   // Color de cada luz con el parpadeo ya aplicado
+}
+
+impl LightMap {
+    pub fn from_maze(maze: &[Vec<char>], ambient: f32) -> Self {
+        let mut lights = Vec::new();
+        for (y, row) in maze.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if let Some(kind) = LightKind::from_cell(cell) {
+                    lights.push(Light::new(kind, x as f32 + 0.5, y as f32 + 0.5));
+                }
+            }
+        }
+
+        let width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+        let visible = compute_visibility(maze, &lights, width);
+
+        let current = lights.iter().map(|light| color::channels(light.color)).collect();
+
+        LightMap {
+            lights,
+            ambient: color::to_fixed(ambient),
+            width,
+            visible,
+            current,
+        }
+    }
+
+    // Vuelve a calcular la oclusión cuando cambia la forma del mapa (p. ej. al abrirse una pared secreta)
+    pub fn rebuild_visibility(&mut self, maze: &[Vec<char>]) {
+        self.visible = compute_visibility(maze, &self.lights, self.width);
+    }
+
+    pub fn set_ambient(&mut self, ambient: f32) {
+        self.ambient = color::to_fixed(ambient);
+    }
+
+    // Recalcula el parpadeo de todas las luces
+    pub fn update(&mut self, time: f32) {
+        for (light, current) in self.lights.iter().zip(self.current.iter_mut()) {
+            let strength = light.intensity * light.flicker(time);
+            let [r, g, b] = color::channels(light.color);
+            *current = [
+                (r as f32 * strength) as u32,
+                (g as f32 * strength) as u32,
+                (b as f32 * strength) as u32,
+            ];
+        }
+    }
+
+    // Luz RGB (punto fijo) que recibe el punto (x, y) del mundo dentro de la celda (cell_x, cell_y)
+    pub fn light_at(&self, cell_x: usize, cell_y: usize, x: f32, y: f32) -> [u32; 3] {
+        let mut total = [self.ambient; 3];
+
+        if cell_x >= self.width {
+            return total;
+        }
+
+        if let Some(visible) = self.visible.get(cell_y * self.width + cell_x) {
+            for &i in visible {
+                let light = &self.lights[i];
+                let distance = ((x - light.x).powi(2) + (y - light.y).powi(2)).sqrt();
+                if distance >= light.radius {
+                    continue;
+                }
+
+                let falloff = 1.0 - distance / light.radius;
+                let attenuation = color::to_fixed(falloff * falloff);
+                for (total, current) in total.iter_mut().zip(self.current[i]) {
+                    *total += (current * attenuation) >> 8;
+                }
+            }
+        }
+
+        total
+    }
+}
+
+// Índices de las luces que ve cada celda
+fn compute_visibility(maze: &[Vec<char>], lights: &[Light], width: usize) -> Vec<Vec<usize>> {
+    let mut visible = vec![Vec::new(); width * maze.len()];
+
+    for (y, row) in maze.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            // Las paredes se iluminan a través de la celda libre que tienen delante;
+            // las puertas y las paredes parciales, dentro de su propia celda, necesitan la suya
+            if is_wall(cell) && !is_door(cell) && !is_partial_wall(cell) {
+                continue;
+            }
+
+            let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+            for (i, light) in lights.iter().enumerate() {
+                let distance = ((cx - light.x).powi(2) + (cy - light.y).powi(2)).sqrt();
+                // Media diagonal de margen para que la luz llegue a los bordes de la celda
+                if distance <= light.radius + 0.71 && line_of_sight(maze, light.x, light.y, cx, cy) {
+                    visible[y * width + x].push(i);
+                }
+            }
+        }
+    }
+
+    visible
+}
+
+// Recorre la línea entre dos puntos buscando paredes (sin contar la celda de destino)
+fn line_of_sight(maze: &[Vec<char>], x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let distance = (dx * dx + dy * dy).sqrt();
+    let steps = (distance / 0.1).ceil() as usize;
+    let target = (x1 as usize, y1 as usize);
+
+    for step in 1..steps {
+        let t = step as f32 / steps as f32;
+        let (x, y) = ((x0 + dx * t) as usize, (y0 + dy * t) as usize);
+        if (x, y) == target {
+            break;
+        }
+        if is_wall(maze[y][x]) && !is_see_through(maze[y][x]) && !is_partial_wall(maze[y][x]) {
+            return false;
+        }
+    }
+
+    true
+}
@@ -1,12 +1,126 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-pub fn load_maze(filename: &str) -> Vec<Vec<char>> {
+// Un nivel es el mapa de caracteres seguido de líneas de metadatos que empiezan con ':'
+// (por ejemplo `:teleport 3 4 20 9 90`); cada una se devuelve separada en palabras, sin los dos puntos.
+pub fn load_level(filename: &str) -> (Vec<Vec<char>>, Vec<Vec<String>>) {
     let file = File::open(filename).unwrap();
     let reader = BufReader::new(file);
 
-    reader
-        .lines()
-        .map(|line| line.unwrap().chars().collect())
-        .collect()
+    let mut maze = Vec::new();
+    let mut directives = Vec::new();
+    for line in reader.lines() {
+        let line = line.unwrap();
+        match line.strip_prefix(':') {
+            Some(directive) => directives.push(directive.split_whitespace().map(String::from).collect()),
+            None => maze.push(line.chars().collect()),
+        }
+    }
+    directives.retain(|words: &Vec<String>| !words.is_empty());
+
+    (maze, directives)
+}
+
+// Celdas de suelo que el jugador puede pisar y que los rayos atraviesan.
+// Las fuentes de luz ('t' antorcha, 'u' brasero, '~' lava), los charcos de fango (';') y
+// de hielo ('='), el aceite ('o') y las llaves
+// ('r' roja, 'b' azul, 'y' amarilla), los tesoros ('$'), los teletransportadores ('@')
+// y los demonios ('d', donde aparecen al cargar el nivel) se colocan sobre el suelo.
+pub fn is_open(cell: char) -> bool {
+    matches!(cell, ' ' | 't' | 'u' | '~' | ';' | '=' | 'o' | '$' | '@' | 'd') || is_key(cell)
+}
+
+// Cuánto cambia la velocidad del jugador al pisar la celda: el fango frena y el hielo hace resbalar
+pub fn floor_speed(cell: char) -> f32 {
+    match cell {
+        ';' => 0.5,
+        '=' => 1.6,
+        _ => 1.0,
+    }
+}
+
+pub fn is_wall(cell: char) -> bool {
+    !is_open(cell)
+}
+
+// Paredes con huecos: barrotes ('|'), rejillas ('+'), ventanas rotas ('w') y alambrado ('x').
+// Bloquean al jugador pero los rayos y la luz pasan a través.
+pub fn is_see_through(cell: char) -> bool {
+    matches!(cell, '|' | '+' | 'w' | 'x')
+}
+
+// Puertas correderas; su estado (abierta o cerrada) vive en `door::Doors`.
+// 'R', 'B' e 'Y' son puertas cerradas con llave que se abren con la llave del mismo color.
+pub fn is_door(cell: char) -> bool {
+    matches!(cell, 'D' | 'R' | 'B' | 'Y')
+}
+
+// Paredes secretas que se pueden empujar; se ven como una pared normal
+pub fn is_push_wall(cell: char) -> bool {
+    cell == '*'
+}
+
+pub fn is_teleport_pad(cell: char) -> bool {
+    cell == '@'
+}
+
+// Paredes portal: su cara libre muestra (y lleva a) la del portal enlazado; ver `teleport::Teleporters`
+pub fn is_portal(cell: char) -> bool {
+    cell == '&'
+}
+
+// Paredes que solo ocupan parte de su celda (columnas, muros finos y diagonales);
+// su forma exacta está en `shape::cell_shape`
+pub fn is_partial_wall(cell: char) -> bool {
+    matches!(cell, 'O' | '[' | ']' | '^' | 'v' | '/' | '\\')
+}
+
+// Espejos: reflejan los rayos en vez de detenerlos
+pub fn is_mirror(cell: char) -> bool {
+    cell == 'M'
+}
+
+pub fn is_key(cell: char) -> bool {
+    matches!(cell, 'r' | 'b' | 'y')
+}
+
+// Llave que necesita una puerta, si está cerrada con llave
+pub fn key_for_door(cell: char) -> Option<char> {
+    match cell {
+        'R' | 'B' | 'Y' => Some(cell.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+pub fn key_color(key: char) -> u32 {
+    match key.to_ascii_lowercase() {
+        'r' => 0xCC2222,
+        'b' => 0x2255DD,
+        'y' => 0xE0C020,
+        _ => 0xFFFFFF,
+    }
+}
+
+// Nombre del color de una llave o puerta, para buscar sus imágenes en el atlas ("key_red", "door_red")
+pub fn key_name(key: char) -> &'static str {
+    match key.to_ascii_lowercase() {
+        'r' => "red",
+        'b' => "blue",
+        'y' => "yellow",
+        _ => "white",
+    }
+}
+
+// Las paredes más altas del nivel; un rayo que pasa por encima de todo lo demás se detiene aquí
+pub const MAX_WALL_HEIGHT: f32 = 2.0;
+
+// Altura de cada tipo de pared en unidades de celda ('_' barrera baja, 'h' media pared, 'T' torre)
+pub fn wall_height(cell: char) -> f32 {
+    match cell {
+        '_' => 0.25,
+        'h' => 0.5,
+        'T' => MAX_WALL_HEIGHT,
+        c if is_wall(c) => 1.0,
+        _ => 0.0,
+    }
 }
@@ -2,52 +2,79 @@ use rand::seq::SliceRandom;
 use rand::Rng;
 use std::fs::File;
 use std::io::{Write, BufWriter};
+use std::collections::{HashMap, HashSet, VecDeque};
+use serde::Deserialize;
+use crate::map::{is_open, is_wall};
 
-pub fn generate_and_save_maze(width: usize, height: usize, filename: &str, path_width: usize) -> (usize, usize, usize, usize) {
-    let mut rng = rand::thread_rng();
-
-    // Adjust dimensions to accommodate path width
-    let maze_width = width * path_width + 1;
-    let maze_height = height * path_width + 1;
+// Algoritmo con el que se excavan las conexiones entre celdas. Todos dan un árbol (un
+// solo camino entre dos celdas) pero con distinto aspecto: pasillos largos y sinuosos
+// (Backtracker), muchos callejones cortos (Prim) o algo intermedio y uniforme (Kruskal).
+#[derive(Clone, Copy, Default, Deserialize)]
+pub enum Algorithm {
+    #[default]
+    Backtracker,
+    Prim,
+    Kruskal,
+}
 
-    let mut maze = vec![vec!['#'; maze_width]; maze_height];
+// Charcos que cubren parte del suelo: lava ('~'), fango que frena (';') o hielo que hace resbalar ('=')
+#[derive(Clone, Copy, Default, Deserialize)]
+pub enum Hazard {
+    None,
+    #[default]
+    Lava,
+    Mud,
+    Ice,
+}
 
-    fn carve_path(x: usize, y: usize, maze: &mut Vec<Vec<char>>, path_width: usize) {
-        for dx in 0..path_width {
-            for dy in 0..path_width {
-                maze[y + dy][x + dx] = ' ';
-            }
+impl Hazard {
+    pub fn cell(self) -> Option<char> {
+        match self {
+            Hazard::None => None,
+            Hazard::Lava => Some('~'),
+            Hazard::Mud => Some(';'),
+            Hazard::Ice => Some('='),
         }
     }
+}
 
-    fn walk(x: usize, y: usize, maze: &mut Vec<Vec<char>>, path_width: usize, rng: &mut impl Rng) {
-        carve_path(x, y, maze, path_width);
+#[derive(Clone, Copy, Default, Deserialize)]
+pub struct MazeStyle {
+    pub algorithm: Algorithm,
+    pub hazard: Hazard,
+    // Dificultad (la pone `level::Difficulty` según el nivel): parte de los callejones sin
+    // salida que se abren para hacer bucles, de 0.0 a 1.0, y cuántos charcos y demonios de
+    // más hay (0.0 los de siempre, 1.0 el doble)
+    #[serde(default)]
+    pub braid: f32,
+    #[serde(default)]
+    pub danger: f32,
+}
 
-        let mut directions = vec![(1, 0), (-1, 0), (0, 1), (0, -1)];
-        directions.shuffle(rng);
+// Cada conexión abierta entre dos celdas se anota como (x, y, dx, dy) desde la celda de origen
+type Connection = (usize, usize, isize, isize);
 
-        for (dx, dy) in directions {
-            let new_x = (x as isize + dx * (path_width as isize + 1)) as usize;
-            let new_y = (y as isize + dy * (path_width as isize + 1)) as usize;
+pub fn generate_and_save_maze(width: usize, height: usize, filename: &str, path_width: usize, style: &MazeStyle) -> (usize, usize, usize, usize) {
+    let mut rng = rand::thread_rng();
 
-            if new_x > 0 && new_x < maze[0].len() - path_width && new_y > 0 && new_y < maze.len() - path_width {
-                if maze[new_y][new_x] == '#' {
-                    // Carve the connection between cells
-                    let mid_x = (x as isize + dx * (path_width as isize + 1) / 2) as usize;
-                    let mid_y = (y as isize + dy * (path_width as isize + 1) / 2) as usize;
-                    carve_path(mid_x, mid_y, maze, path_width);
+    // Adjust dimensions to accommodate path width
+    let maze_width = width * path_width + 1;
+    let maze_height = height * path_width + 1;
 
-                    carve_path(new_x, new_y, maze, path_width);
-                    walk(new_x, new_y, maze, path_width, rng);
-                }
-            }
-        }
-    }
+    let mut maze = vec![vec!['#'; maze_width]; maze_height];
 
     let start_x = path_width;
     let start_y = path_width;
 
-    walk(start_x, start_y, &mut maze, path_width, &mut rng);
+    let connections = match style.algorithm {
+        Algorithm::Backtracker => {
+            let mut connections = Vec::new();
+            walk(start_x, start_y, &mut maze, path_width, &mut connections, &mut rng);
+            connections
+        }
+        Algorithm::Prim => prim(&mut maze, (start_x, start_y), path_width, &mut rng),
+        Algorithm::Kruskal => kruskal(&mut maze, path_width, &mut rng),
+    };
 
     // Place the start and goal points
     maze[start_y][start_x] = 'p'; // Start point
@@ -55,12 +82,692 @@ pub fn generate_and_save_maze(width: usize, height: usize, filename: &str, path_
     let goal_y = maze_height - path_width - 1;
     maze[goal_y][goal_x] = 'g'; // Goal point
 
+    let mut graph = cell_graph(&connections, path_width);
+    place_doors(&mut maze, &connections, path_width, &mut rng);
+    let locked = place_locked_doors(&mut maze, &graph, (start_x, start_y), (goal_x, goal_y), path_width, &mut rng);
+    place_braids(&mut maze, &mut graph, &locked, path_width, style.braid, &mut rng);
+    place_wall_heights(&mut maze, path_width, &mut rng);
+    place_mirrors(&mut maze, &graph, path_width, &mut rng);
+    place_lights(&mut maze, width * height, style, &mut rng);
+    place_secrets(&mut maze, &graph, &locked, path_width, &mut rng);
+    let mut directives = place_teleporters(&mut maze, &graph, &locked, path_width, &mut rng);
+    place_partial_walls(&mut maze, &graph, path_width, &mut rng);
+    directives.extend(place_decals(&maze, &graph, (start_x, start_y), (goal_x, goal_y), path_width, &mut rng));
+
     // Write the maze to a file
     let mut file = BufWriter::new(File::create(filename).expect("Could not create maze file"));
     for row in maze.iter() {
         writeln!(file, "{}", row.iter().collect::<String>()).expect("Could not write to maze file");
     }
+    for directive in directives {
+        writeln!(file, ":{}", directive).expect("Could not write to maze file");
+    }
 
     // Return both the start and goal positions
     (start_x, start_y, goal_x, goal_y)
 }
+
+fn carve_path(x: usize, y: usize, maze: &mut [Vec<char>], path_width: usize) {
+    for dx in 0..path_width {
+        for dy in 0..path_width {
+            maze[y + dy][x + dx] = ' ';
+        }
+    }
+}
+
+// Abre el paso de la celda (x, y) a su vecina en la dirección (dx, dy) y excava las dos
+fn carve_connection(x: usize, y: usize, dx: isize, dy: isize, maze: &mut [Vec<char>], path_width: usize) {
+    let stride = path_width as isize + 1;
+    let mid_x = (x as isize + dx * stride / 2) as usize;
+    let mid_y = (y as isize + dy * stride / 2) as usize;
+    carve_path(x, y, maze, path_width);
+    carve_path(mid_x, mid_y, maze, path_width);
+    carve_path((x as isize + dx * stride) as usize, (y as isize + dy * stride) as usize, maze, path_width);
+}
+
+// Esquinas de todas las celdas que caben en el mapa
+fn grid_cells(maze: &[Vec<char>], path_width: usize) -> HashSet<Cell> {
+    let stride = path_width + 1;
+    let xs: Vec<usize> = (path_width..maze[0].len() - path_width).step_by(stride).collect();
+    let ys: Vec<usize> = (path_width..maze.len() - path_width).step_by(stride).collect();
+    ys.iter().flat_map(|&y| xs.iter().map(move |&x| (x, y))).collect()
+}
+
+// Vecinas de una celda en las cuatro direcciones, con la dirección
+fn grid_neighbours(cells: &HashSet<Cell>, (x, y): Cell, path_width: usize) -> Vec<(Cell, isize, isize)> {
+    let stride = path_width as isize + 1;
+    [(1, 0), (-1, 0), (0, 1), (0, -1)]
+        .into_iter()
+        .map(|(dx, dy)| (((x as isize + dx * stride) as usize, (y as isize + dy * stride) as usize), dx, dy))
+        .filter(|(next, _, _)| cells.contains(next))
+        .collect()
+}
+
+// Backtracker recursivo: avanza al azar mientras puede y retrocede al quedarse sin salida
+fn walk(x: usize, y: usize, maze: &mut Vec<Vec<char>>, path_width: usize, connections: &mut Vec<Connection>, rng: &mut impl Rng) {
+    carve_path(x, y, maze, path_width);
+
+    let mut directions = vec![(1, 0), (-1, 0), (0, 1), (0, -1)];
+    directions.shuffle(rng);
+
+    for (dx, dy) in directions {
+        let new_x = (x as isize + dx * (path_width as isize + 1)) as usize;
+        let new_y = (y as isize + dy * (path_width as isize + 1)) as usize;
+
+        if new_x > 0 && new_x < maze[0].len() - path_width && new_y > 0 && new_y < maze.len() - path_width {
+            if maze[new_y][new_x] == '#' {
+                // Carve the connection between cells
+                carve_connection(x, y, dx, dy, maze, path_width);
+                connections.push((x, y, dx, dy));
+                walk(new_x, new_y, maze, path_width, connections, rng);
+            }
+        }
+    }
+}
+
+// Prim: el laberinto crece desde el inicio uniendo cada vez una celda nueva al azar de su borde
+fn prim(maze: &mut [Vec<char>], start: Cell, path_width: usize, rng: &mut impl Rng) -> Vec<Connection> {
+    let cells = grid_cells(maze, path_width);
+    let mut inside = HashSet::from([start]);
+    let mut frontier: Vec<(Cell, isize, isize)> = grid_neighbours(&cells, start, path_width).into_iter().map(|(_, dx, dy)| (start, dx, dy)).collect();
+    let mut connections = Vec::new();
+    carve_path(start.0, start.1, maze, path_width);
+
+    while !frontier.is_empty() {
+        let (from, dx, dy) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+        let stride = path_width as isize + 1;
+        let to = ((from.0 as isize + dx * stride) as usize, (from.1 as isize + dy * stride) as usize);
+        if !inside.insert(to) {
+            continue;
+        }
+        carve_connection(from.0, from.1, dx, dy, maze, path_width);
+        connections.push((from.0, from.1, dx, dy));
+        frontier.extend(grid_neighbours(&cells, to, path_width).into_iter().filter(|(next, _, _)| !inside.contains(next)).map(|(_, dx, dy)| (to, dx, dy)));
+    }
+    connections
+}
+
+// Kruskal: recorre todas las paredes entre celdas en orden aleatorio y abre las que
+// unen dos zonas todavía separadas
+fn kruskal(maze: &mut [Vec<char>], path_width: usize, rng: &mut impl Rng) -> Vec<Connection> {
+    let cells = grid_cells(maze, path_width);
+    let mut edges: Vec<(Cell, isize, isize)> = cells
+        .iter()
+        .flat_map(|&cell| grid_neighbours(&cells, cell, path_width).into_iter().filter(|&(_, dx, dy)| dx > 0 || dy > 0).map(move |(_, dx, dy)| (cell, dx, dy)))
+        .collect();
+    edges.sort();
+    edges.shuffle(rng);
+
+    // Cada celda apunta a otra de su zona; la raíz representa a toda la zona
+    let mut parent: HashMap<Cell, Cell> = cells.iter().map(|&cell| (cell, cell)).collect();
+    fn root(parent: &mut HashMap<Cell, Cell>, cell: Cell) -> Cell {
+        let next = parent[&cell];
+        if next == cell {
+            return cell;
+        }
+        let top = root(parent, next);
+        parent.insert(cell, top);
+        top
+    }
+
+    let mut connections = Vec::new();
+    for &cell in &cells {
+        carve_path(cell.0, cell.1, maze, path_width);
+    }
+    let stride = path_width as isize + 1;
+    for (from, dx, dy) in edges {
+        let to = ((from.0 as isize + dx * stride) as usize, (from.1 as isize + dy * stride) as usize);
+        let (a, b) = (root(&mut parent, from), root(&mut parent, to));
+        if a != b {
+            parent.insert(a, b);
+            carve_connection(from.0, from.1, dx, dy, maze, path_width);
+            connections.push((from.0, from.1, dx, dy));
+        }
+    }
+    connections
+}
+
+// Cierra algunas conexiones con un marco de pared y una puerta corredera en el centro
+fn place_doors(maze: &mut [Vec<char>], connections: &[Connection], path_width: usize, rng: &mut impl Rng) {
+    for &(x, y, dx, dy) in connections {
+        if !rng.gen_bool(0.25) {
+            continue;
+        }
+        build_door_frame(maze, x, y, dx, dy, path_width, 'D');
+    }
+}
+
+// Esquina superior izquierda de una celda del laberinto
+type Cell = (usize, usize);
+
+// Las celdas del laberinto forman un árbol; cada una con las vecinas a las que está conectada
+fn cell_graph(connections: &[Connection], path_width: usize) -> HashMap<Cell, Vec<Cell>> {
+    let stride = (path_width + 1) as isize;
+    let mut graph: HashMap<Cell, Vec<Cell>> = HashMap::new();
+    for &(x, y, dx, dy) in connections {
+        let next = ((x as isize + dx * stride) as usize, (y as isize + dy * stride) as usize);
+        graph.entry((x, y)).or_default().push(next);
+        graph.entry(next).or_default().push((x, y));
+    }
+    graph
+}
+
+// Celdas alcanzables desde `start` sin cruzar las conexiones de `blocked`, cada una con su predecesora
+fn reachable(graph: &HashMap<Cell, Vec<Cell>>, start: Cell, blocked: &HashSet<(Cell, Cell)>) -> HashMap<Cell, Cell> {
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    parents.insert(start, start);
+    while let Some(cell) = queue.pop_front() {
+        for &next in graph.get(&cell).into_iter().flatten() {
+            if !parents.contains_key(&next) && !blocked.contains(&(cell, next)) && !blocked.contains(&(next, cell)) {
+                parents.insert(next, cell);
+                queue.push_back(next);
+            }
+        }
+    }
+    parents
+}
+
+// Recorrido de la solución desde el inicio hasta la celda que contiene la meta
+fn solution_path(graph: &HashMap<Cell, Vec<Cell>>, start: Cell, goal: (usize, usize), path_width: usize) -> Vec<Cell> {
+    let stride = path_width + 1;
+    let snap = |v: usize| (v - path_width) / stride * stride + path_width;
+    let goal_cell = (snap(goal.0), snap(goal.1));
+
+    let parents = reachable(graph, start, &HashSet::new());
+    let mut solution = vec![goal_cell];
+    while let Some(&parent) = parents.get(solution.last().unwrap()) {
+        if parent == *solution.last().unwrap() {
+            break;
+        }
+        solution.push(parent);
+    }
+    solution.reverse();
+    solution
+}
+
+// Cierra con llave hasta tres puertas a lo largo de la solución y esconde cada llave
+// en la parte del laberinto a la que se llega desde el inicio sin cruzar esa puerta
+// ni las siguientes, así el nivel siempre se puede resolver. Devuelve las conexiones cerradas.
+fn place_locked_doors(
+    maze: &mut [Vec<char>],
+    graph: &HashMap<Cell, Vec<Cell>>,
+    start: Cell,
+    goal: (usize, usize),
+    path_width: usize,
+    rng: &mut impl Rng,
+) -> Vec<(Cell, Cell)> {
+    let solution = solution_path(graph, start, goal, path_width);
+
+    let locks = ['R', 'B', 'Y'];
+    let count = locks.len().min(solution.len().saturating_sub(2) / 2);
+    if count == 0 {
+        return Vec::new();
+    }
+
+    // Puertas repartidas a lo largo de la solución
+    let edges: Vec<(Cell, Cell)> = (1..=count)
+        .map(|i| {
+            let j = i * (solution.len() - 1) / (count + 1);
+            (solution[j], solution[j + 1])
+        })
+        .collect();
+    let on_solution: HashSet<Cell> = solution.iter().copied().collect();
+
+    for (i, &(from, to)) in edges.iter().enumerate() {
+        let door = locks[i];
+        let dx = (to.0 as isize - from.0 as isize).signum();
+        let dy = (to.1 as isize - from.1 as isize).signum();
+        build_door_frame(maze, from.0, from.1, dx, dy, path_width, door);
+
+        // Con esta puerta y las siguientes cerradas, la llave tiene que quedar a este lado;
+        // se prefieren los callejones fuera de la solución para obligar a explorar
+        let blocked: HashSet<_> = edges[i..].iter().copied().collect();
+        let free = |&(x, y): &Cell| maze[y + path_width / 2][x + path_width / 2] == ' ';
+        let region: Vec<Cell> = reachable(graph, start, &blocked).into_keys().filter(|cell| *cell != start && free(cell)).collect();
+        let side_branches: Vec<Cell> = region.iter().copied().filter(|cell| !on_solution.contains(cell)).collect();
+        let candidates = if side_branches.is_empty() { &region } else { &side_branches };
+
+        if let Some(&(x, y)) = candidates.choose(rng) {
+            let (kx, ky) = (x + path_width / 2, y + path_width / 2);
+            maze[ky][kx] = door.to_ascii_lowercase();
+        }
+    }
+
+    edges
+}
+
+// Abre parte de los callejones sin salida hacia una celda vecina, así hay más de un camino
+// y el laberinto tiene bucles. Como los atajos secretos, solo une celdas de la misma zona de
+// llaves para no saltarse ninguna puerta.
+fn place_braids(
+    maze: &mut [Vec<char>],
+    graph: &mut HashMap<Cell, Vec<Cell>>,
+    locked: &[(Cell, Cell)],
+    path_width: usize,
+    amount: f32,
+    rng: &mut impl Rng,
+) {
+    if amount <= 0.0 {
+        return;
+    }
+    let stride = (path_width + 1) as isize;
+    let locked: HashSet<(Cell, Cell)> = locked.iter().copied().collect();
+    let mut dead_ends: Vec<Cell> = graph.iter().filter(|(_, next)| next.len() == 1).map(|(&cell, _)| cell).collect();
+    dead_ends.sort();
+    dead_ends.shuffle(rng);
+
+    for cell in dead_ends {
+        // Puede haber dejado de serlo al abrir el de al lado
+        if graph[&cell].len() != 1 || !rng.gen_bool(amount.min(1.0) as f64) {
+            continue;
+        }
+        let zone = reachable(graph, cell, &locked);
+        let mut sides = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+        sides.shuffle(rng);
+        let side = sides.into_iter().map(|(dx, dy)| {
+            let next = ((cell.0 as isize + dx * stride) as usize, (cell.1 as isize + dy * stride) as usize);
+            (next, dx, dy)
+        }).find(|(next, _, _)| zone.contains_key(next) && !graph[&cell].contains(next));
+
+        if let Some((next, dx, dy)) = side {
+            // Se quita el tramo de pared que separa las dos celdas, como en `build_door_frame`
+            let line = |origin: usize, d: isize| if d > 0 { origin + path_width } else { origin - 1 };
+            for i in 0..path_width {
+                let (fx, fy) = if dx != 0 { (line(cell.0, dx), cell.1 + i) } else { (cell.0 + i, line(cell.1, dy)) };
+                maze[fy][fx] = ' ';
+            }
+            graph.entry(cell).or_default().push(next);
+            graph.entry(next).or_default().push(cell);
+        }
+    }
+}
+
+// Paredes secretas: salas del tesoro escondidas en el borde grueso del laberinto y
+// atajos entre celdas vecinas que no estaban conectadas. Un atajo solo une celdas
+// a las que ya se llega sin cruzar ninguna puerta con llave, para no saltarse ninguna.
+fn place_secrets(
+    maze: &mut [Vec<char>],
+    graph: &HashMap<Cell, Vec<Cell>>,
+    locked: &[(Cell, Cell)],
+    path_width: usize,
+    rng: &mut impl Rng,
+) {
+    // El bloque necesita dos celdas libres detrás para deslizarse
+    if path_width < 4 {
+        return;
+    }
+    let stride = path_width + 1;
+    let middle = path_width / 2;
+
+    // Una sala en el borde superior y otra en el izquierdo
+    let top: Vec<Cell> = graph.keys().copied().filter(|&(_, y)| y == path_width).collect();
+    if let Some(&(x, _)) = top.choose(rng) {
+        for row in maze.iter_mut().take(path_width - 1).skip(1) {
+            row[x..x + path_width].fill(' ');
+        }
+        maze[path_width - 1][x + middle] = '*';
+        maze[1][x] = '$';
+        maze[1][x + path_width - 1] = '$';
+    }
+    let left: Vec<Cell> = graph.keys().copied().filter(|&(x, _)| x == path_width).collect();
+    if let Some(&(_, y)) = left.choose(rng) {
+        for row in maze.iter_mut().skip(y).take(path_width) {
+            row[1..path_width - 1].fill(' ');
+        }
+        maze[y + middle][path_width - 1] = '*';
+        maze[y][1] = '$';
+        maze[y + path_width - 1][1] = '$';
+    }
+
+    // Atajos: parejas de celdas vecinas sin conexión dentro de la misma zona de llaves
+    let locked: HashSet<(Cell, Cell)> = locked.iter().copied().collect();
+    let mut pairs: Vec<(Cell, isize, isize)> = Vec::new();
+    for &cell in graph.keys() {
+        for (dx, dy) in [(1, 0), (0, 1)] {
+            let next = (cell.0 + dx * stride, cell.1 + dy * stride);
+            if graph.contains_key(&next) && !graph[&cell].contains(&next) {
+                pairs.push((cell, dx as isize, dy as isize));
+            }
+        }
+    }
+    pairs.shuffle(rng);
+
+    let mut shortcuts = 2;
+    for (cell, dx, dy) in pairs {
+        if shortcuts == 0 {
+            break;
+        }
+        let next = ((cell.0 as isize + dx * stride as isize) as usize, (cell.1 as isize + dy * stride as isize) as usize);
+        if !reachable(graph, cell, &locked).contains_key(&next) {
+            continue;
+        }
+
+        // Un punto de la línea de pared con dos celdas libres a cada lado
+        let free = |x: isize, y: isize| maze[y as usize][x as usize] == ' ';
+        let spot = (1..path_width - 1).map(|i| {
+            if dx != 0 { ((cell.0 + path_width) as isize, (cell.1 + i) as isize) } else { ((cell.0 + i) as isize, (cell.1 + path_width) as isize) }
+        }).find(|&(x, y)| {
+            maze[y as usize][x as usize] == '#' && (1..=2).all(|d| free(x + dx * d, y + dy * d) && free(x - dx * d, y - dy * d))
+        });
+
+        if let Some((x, y)) = spot {
+            maze[y as usize][x as usize] = '*';
+            shortcuts -= 1;
+        }
+    }
+}
+
+// Las dos celdas a cada lado de cada pared secreta ('*'): desde ellas se empuja y por
+// ellas se desliza, así que tienen que quedar libres
+fn push_wall_lanes(maze: &[Vec<char>]) -> HashSet<Cell> {
+    let mut lanes = HashSet::new();
+    for (y, row) in maze.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            if cell != '*' {
+                continue;
+            }
+            for d in 1..=2 {
+                lanes.extend([(x + d, y), (x.wrapping_sub(d), y), (x, y + d), (x, y.wrapping_sub(d))]);
+            }
+        }
+    }
+    lanes
+}
+
+// Levanta un muro sobre la línea de pared que separa la celda (x, y) de su vecina
+// en la dirección (dx, dy), dejando `door` en el centro
+fn build_door_frame(maze: &mut [Vec<char>], x: usize, y: usize, dx: isize, dy: isize, path_width: usize, door: char) {
+    // La línea de pared queda justo después de la celda en la dirección de la conexión
+    let line = |origin: usize, d: isize| if d > 0 { origin + path_width } else { origin - 1 };
+
+    for i in 0..path_width {
+        let (fx, fy) = if dx != 0 { (line(x, dx), y + i) } else { (x + i, line(y, dy)) };
+        maze[fy][fx] = if i == path_width / 2 { door } else { '#' };
+    }
+}
+
+// Da relieve al laberinto: torres en los cruces de paredes y tramos de muro
+// bajos o con huecos por los que se puede ver (pero no pasar)
+fn place_wall_heights(maze: &mut [Vec<char>], path_width: usize, rng: &mut impl Rng) {
+    let rows = maze.len();
+    let cols = maze[0].len();
+
+    for y in 1..rows - 1 {
+        for x in 1..cols - 1 {
+            if maze[y][x] != '#' {
+                continue;
+            }
+
+            let neighbours = [(x, y - 1), (x, y + 1), (x - 1, y), (x + 1, y)]
+                .iter()
+                .filter(|&&(nx, ny)| is_wall(maze[ny][nx]))
+                .count();
+
+            // Cruces y esquinas con tres o más muros alrededor
+            if neighbours >= 3 && rng.gen_bool(0.3) {
+                maze[y][x] = 'T';
+                continue;
+            }
+
+            // Inicio de un tramo horizontal o vertical que se rebaja o se abre
+            if neighbours == 2 && rng.gen_bool(0.03) {
+                let low = *['h', '_', '|', '+', 'w', 'x'].choose(rng).unwrap();
+                let (dx, dy) = if is_wall(maze[y][x + 1]) { (1, 0) } else { (0, 1) };
+                for step in 0..path_width {
+                    let (sx, sy) = (x + dx * step, y + dy * step);
+                    if sx >= cols - 1 || sy >= rows - 1 || maze[sy][sx] != '#' {
+                        break;
+                    }
+                    maze[sy][sx] = low;
+                }
+            }
+        }
+    }
+}
+
+// Una pareja de teletransportadores y otra de portales, cada una dentro de una misma
+// zona de llaves para que no sirvan de atajo a una puerta cerrada. Devuelve los enlaces
+// como líneas de metadatos del nivel (ver `map::load_level`).
+fn place_teleporters(
+    maze: &mut [Vec<char>],
+    graph: &HashMap<Cell, Vec<Cell>>,
+    locked: &[(Cell, Cell)],
+    path_width: usize,
+    rng: &mut impl Rng,
+) -> Vec<String> {
+    let mut directives = Vec::new();
+    let locked: HashSet<(Cell, Cell)> = locked.iter().copied().collect();
+    let middle = path_width / 2;
+
+    // La zona más grande entre puertas con llave
+    let mut region: Vec<Cell> = Vec::new();
+    let mut seen: HashSet<Cell> = HashSet::new();
+    for &cell in graph.keys() {
+        if seen.contains(&cell) {
+            continue;
+        }
+        let zone: Vec<Cell> = reachable(graph, cell, &locked).into_keys().collect();
+        seen.extend(zone.iter().copied());
+        if zone.len() > region.len() {
+            region = zone;
+        }
+    }
+    region.shuffle(rng);
+
+    // Teletransportadores en el centro de dos celdas libres, uno de ida y otro de vuelta
+    let lanes = push_wall_lanes(maze);
+    let pads: Vec<Cell> = region.iter().map(|&(x, y)| (x + middle, y + middle)).filter(|&(x, y)| maze[y][x] == ' ' && !lanes.contains(&(x, y))).take(2).collect();
+    if let [(ax, ay), (bx, by)] = pads[..] {
+        maze[ay][ax] = '@';
+        maze[by][bx] = '@';
+        directives.push(format!("teleport {} {} {} {}", ax, ay, bx, by));
+        directives.push(format!("teleport {} {} {} {}", bx, by, ax, ay));
+    }
+
+    // Portales en paredes lisas con una sola cara libre (los fondos de saco y el borde)
+    let open_sides = |maze: &[Vec<char>], x: usize, y: usize| {
+        [(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))]
+            .iter()
+            .filter(|&&(nx, ny)| maze.get(ny).and_then(|row| row.get(nx)).is_some_and(|&c| is_open(c)))
+            .count()
+    };
+    let mut portals: Vec<Cell> = Vec::new();
+    for &(x, y) in &region {
+        let sides = [(x + path_width, y + middle), (x - 1, y + middle), (x + middle, y + path_width), (x + middle, y - 1)];
+        let wall = sides.into_iter().find(|&(wx, wy)| {
+            maze.get(wy).and_then(|row| row.get(wx)) == Some(&'#') && open_sides(maze, wx, wy) == 1
+        });
+        if let Some(wall) = wall {
+            portals.push(wall);
+            if portals.len() == 2 {
+                break;
+            }
+        }
+    }
+    if let [(ax, ay), (bx, by)] = portals[..] {
+        maze[ay][ax] = '&';
+        maze[by][bx] = '&';
+        directives.push(format!("portal {} {} {} {}", ax, ay, bx, by));
+    }
+
+    directives
+}
+
+// Salas de espejos y de carne: todas las paredes lisas alrededor de una o dos celdas
+// se vuelven espejos ('M'), y las de otras tantas, paredes de carne que laten ('F')
+fn place_mirrors(maze: &mut [Vec<char>], graph: &HashMap<Cell, Vec<Cell>>, path_width: usize, rng: &mut impl Rng) {
+    let cells: Vec<Cell> = graph.keys().copied().collect();
+    let halls = (cells.len() / 30).clamp(1, 2);
+
+    for (n, &(x, y)) in cells.choose_multiple(rng, halls * 2).enumerate() {
+        let skin = if n < halls { 'M' } else { 'F' };
+        for i in 0..path_width {
+            for (wx, wy) in [(x - 1, y + i), (x + path_width, y + i), (x + i, y - 1), (x + i, y + path_width)] {
+                if let Some(cell) = maze.get_mut(wy).and_then(|row| row.get_mut(wx)) {
+                    if *cell == '#' {
+                        *cell = skin;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Columnatas, esquinas achaflanadas y biombos (un muro fino que cruza la celda dejando
+// paso por los dos extremos). Solo ocupan suelo libre, así que no cierran ningún camino.
+fn place_partial_walls(maze: &mut [Vec<char>], graph: &HashMap<Cell, Vec<Cell>>, path_width: usize, rng: &mut impl Rng) {
+    if path_width < 4 {
+        return;
+    }
+    let last = path_width - 1;
+    let middle = path_width / 2;
+
+    let lanes = push_wall_lanes(maze);
+    let mut cells: Vec<Cell> = graph.keys().copied().collect();
+    cells.shuffle(rng);
+
+    for (x, y) in cells {
+        let block = (y..y + path_width).flat_map(|row| (x..x + path_width).map(move |col| (col, row)));
+        if block.clone().any(|(col, row)| matches!(maze[row][col], 'p' | 'g')) {
+            continue;
+        }
+        let place = |maze: &mut [Vec<char>], col: usize, row: usize, cell: char| {
+            if maze[row][col] == ' ' && !lanes.contains(&(col, row)) {
+                maze[row][col] = cell;
+            }
+        };
+
+        match rng.gen_range(0..10) {
+            // Cuatro columnas alrededor del centro
+            0 => {
+                for (col, row) in [(1, 1), (last - 1, 1), (1, last - 1), (last - 1, last - 1)] {
+                    place(maze, x + col, y + row, 'O');
+                }
+            }
+            // Chaflanes en las esquinas que tocan pared por los dos lados
+            1 | 2 => {
+                let corners = [(0, 0, -1, -1, '/'), (last, 0, 1, -1, '\\'), (0, last, -1, 1, '\\'), (last, last, 1, 1, '/')];
+                for (col, row, dx, dy, diagonal) in corners {
+                    let (cx, cy) = (x + col, y + row);
+                    let walled = is_wall(maze[cy][(cx as isize + dx) as usize]) && is_wall(maze[(cy as isize + dy) as usize][cx]);
+                    if walled {
+                        place(maze, cx, cy, diagonal);
+                    }
+                }
+            }
+            // Biombo de este a oeste por el centro de la celda
+            3 => {
+                for col in 1..last {
+                    place(maze, x + col, y + middle, '^');
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Calcomanías: flechas en la pared de enfrente allí donde la solución gira (no en
+// todos los giros, para no dar el laberinto resuelto) y manchas, zarpazos y runas
+// repartidos por el resto. Devuelve las líneas `decal` del nivel.
+fn place_decals(
+    maze: &[Vec<char>],
+    graph: &HashMap<Cell, Vec<Cell>>,
+    start: Cell,
+    goal: (usize, usize),
+    path_width: usize,
+    rng: &mut impl Rng,
+) -> Vec<String> {
+    let stride = (path_width + 1) as isize;
+    let middle = path_width / 2;
+    let mut directives = Vec::new();
+    let mut used = HashSet::new(); // Una sola calcomanía por cara
+
+    // Pared plana en la dirección (dx, dy) desde el centro de la celda, con la cara que mira hacia ella
+    let wall_ahead = |(x, y): Cell, (dx, dy): (isize, isize)| {
+        let beside = |corner: usize, d: isize| match d {
+            1 => corner + path_width,
+            -1 => corner - 1,
+            _ => corner + middle,
+        };
+        let (wx, wy) = (beside(x, dx), beside(y, dy));
+        let face = match (dx, dy) {
+            (1, _) => "west",
+            (-1, _) => "east",
+            (_, 1) => "north",
+            _ => "south",
+        };
+        let cell = maze.get(wy).and_then(|row| row.get(wx));
+        cell.filter(|&&c| matches!(c, '#' | 'T' | 'F')).map(|_| (wx, wy, face))
+    };
+    let direction = |from: Cell, to: Cell| ((to.0 as isize - from.0 as isize) / stride, (to.1 as isize - from.1 as isize) / stride);
+
+    let solution = solution_path(graph, start, goal, path_width);
+    for steps in solution.windows(3) {
+        let heading = direction(steps[0], steps[1]);
+        let turn = direction(steps[1], steps[2]);
+        if heading == turn || !rng.gen_bool(0.5) {
+            continue;
+        }
+        // Con la y hacia abajo, girar a la derecha es un producto cruzado positivo
+        let kind = if heading.0 * turn.1 - heading.1 * turn.0 > 0 { "arrow_right" } else { "arrow_left" };
+        if let Some((x, y, face)) = wall_ahead(steps[1], heading).filter(|&wall| used.insert(wall)) {
+            directives.push(format!("decal {} {} {} {}", x, y, face, kind));
+        }
+    }
+
+    let cells: Vec<Cell> = graph.keys().copied().collect();
+    for &cell in cells.choose_multiple(rng, cells.len() / 5) {
+        let kind = ["blood", "claws", "rune"].choose(rng).unwrap();
+        let side = *[(1, 0), (-1, 0), (0, 1), (0, -1)].choose(rng).unwrap();
+        if let Some((x, y, face)) = wall_ahead(cell, side).filter(|&wall| used.insert(wall)) {
+            directives.push(format!("decal {} {} {} {}", x, y, face, kind));
+        }
+    }
+
+    directives
+}
+
+// Reparte antorchas junto a las paredes, braseros y charcos del peligro del nivel en los
+// pasillos y frascos de aceite para el farol del modo oscuridad, además de los demonios que rondan
+fn place_lights(maze: &mut Vec<Vec<char>>, cell_count: usize, style: &MazeStyle, rng: &mut impl Rng) {
+    let mut candidates = Vec::new();
+    for (y, row) in maze.iter().enumerate().take(maze.len() - 1).skip(1) {
+        for (x, &cell) in row.iter().enumerate().take(row.len() - 1).skip(1) {
+            if cell == ' ' {
+                candidates.push((x, y));
+            }
+        }
+    }
+    candidates.shuffle(rng);
+
+    let next_to_wall = |maze: &Vec<Vec<char>>, x: usize, y: usize| {
+        is_wall(maze[y - 1][x]) || is_wall(maze[y + 1][x]) || is_wall(maze[y][x - 1]) || is_wall(maze[y][x + 1])
+    };
+
+    let mut torches = cell_count / 2;
+    let mut braziers = cell_count / 10;
+    let more = 1.0 + style.danger.max(0.0);
+    let mut pools = if style.hazard.cell().is_some() { (cell_count as f32 / 12.0 * more) as usize } else { 0 };
+    let mut oil_flasks = cell_count / 8;
+    let mut demons = (cell_count as f32 / 10.0 * more) as usize;
+
+    for (x, y) in candidates {
+        if torches > 0 && next_to_wall(maze, x, y) {
+            maze[y][x] = 't';
+            torches -= 1;
+        } else if braziers > 0 && !next_to_wall(maze, x, y) {
+            maze[y][x] = 'u';
+            braziers -= 1;
+        } else if let Some(pool) = style.hazard.cell().filter(|_| pools > 0) {
+            maze[y][x] = pool;
+            pools -= 1;
+        } else if oil_flasks > 0 {
+            maze[y][x] = 'o'; // Aceite para el farol
+            oil_flasks -= 1;
+        } else if demons > 0 && !next_to_wall(maze, x, y) {
+            maze[y][x] = 'd';
+            demons -= 1;
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+use std::rc::Rc;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::assets::Assets;
+use crate::directional::{DirectionalSprite, SheetState, SpriteSheet, DIRECTIONS};
+use crate::map::is_open;
+use crate::player::Player;
+use crate::props::draw;
+use crate::sprite::Billboard;
+use crate::texture::Texture;
+use crate::world::World;
+
+// Demonios ('d' en el mapa) que deambulan de celda en celda, se vuelven hacia el
+// jugador y le atacan cuando lo tienen cerca, y mueren si pisan la lava.
+
+const SPEED: f32 = 1.2;        // Celdas por segundo
+const ATTACK_RANGE: f32 = 1.3;
+const SCALE: f32 = 0.9;
+const SHEET_FILE: &str = "sprites/demon.sheet";
+
+pub struct Monster {
+    pub x: f32,
+    pub y: f32,
+    pub facing: f32,
+    sprite: DirectionalSprite,
+    target: Option<(f32, f32)>, // Centro de la celda hacia la que camina
+    rest: f32,                  // Segundos que le quedan quieto antes de moverse otra vez
+}
+
+impl Monster {
+    pub fn new(x: f32, y: f32, sheet: Rc<SpriteSheet>) -> Self {
+        Monster { x, y, facing: 0.0, sprite: DirectionalSprite::new(sheet, "idle"), target: None, rest: 0.0 }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.sprite.state() == "die"
+    }
+
+    // La lava no deja cadáver: desaparece al acabar de morir
+    pub fn is_gone(&self) -> bool {
+        self.is_dead() && self.sprite.finished()
+    }
+
+    pub fn update(&mut self, delta_time: f32, maze: &[Vec<char>], world: &World, player: &Player) {
+        self.sprite.update(delta_time);
+        if self.is_dead() {
+            return;
+        }
+
+        let (cell_x, cell_y) = (self.x as usize, self.y as usize);
+        if maze[cell_y][cell_x] == '~' {
+            self.sprite.set_state("die");
+            return;
+        }
+
+        let (to_player_x, to_player_y) = (player.pos.x - self.x, player.pos.y - self.y);
+        if (to_player_x * to_player_x + to_player_y * to_player_y).sqrt() < ATTACK_RANGE {
+            self.facing = to_player_y.atan2(to_player_x);
+            self.sprite.set_state("attack");
+            return;
+        }
+
+        let Some((target_x, target_y)) = self.target else {
+            self.rest -= delta_time;
+            if self.rest > 0.0 {
+                self.sprite.set_state("idle");
+                return;
+            }
+            // Elige una celda vecina por la que se pueda pasar
+            let mut rng = rand::thread_rng();
+            let mut directions = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+            directions.shuffle(&mut rng);
+            let free = directions.iter().map(|&(dx, dy)| ((cell_x as isize + dx) as usize, (cell_y as isize + dy) as usize)).find(|&(x, y)| {
+                maze.get(y).and_then(|row| row.get(x)).is_some_and(|&c| is_open(c)) && !world.blocks(maze, x, y)
+            });
+            match free {
+                Some((x, y)) => self.target = Some((x as f32 + 0.5, y as f32 + 0.5)),
+                None => self.rest = rng.gen_range(0.5..2.0),
+            }
+            return;
+        };
+
+        let (dx, dy) = (target_x - self.x, target_y - self.y);
+        let distance = (dx * dx + dy * dy).sqrt();
+        let step = SPEED * delta_time;
+        self.sprite.set_state("walk");
+        if distance <= step {
+            self.x = target_x;
+            self.y = target_y;
+            self.target = None;
+            // A veces se queda un rato parado al llegar
+            if rand::thread_rng().gen_bool(0.3) {
+                self.rest = rand::thread_rng().gen_range(0.5..2.0);
+            }
+        } else {
+            self.facing = dy.atan2(dx);
+            self.x += dx / distance * step;
+            self.y += dy / distance * step;
+        }
+    }
+
+    pub fn billboard(&self, viewer_x: f32, viewer_y: f32) -> Billboard<'_> {
+        Billboard {
+            x: self.x,
+            y: self.y,
+            scale: SCALE,
+            texture: self.sprite.frame(self.x, self.y, self.facing, viewer_x, viewer_y),
+        }
+    }
+}
+
+// Crea un demonio por cada 'd' del mapa y deja el suelo libre en su lugar
+pub fn monsters_from_maze(maze: &mut [Vec<char>], sheet: &Rc<SpriteSheet>) -> Vec<Monster> {
+    let mut monsters = Vec::new();
+    for (y, row) in maze.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            if *cell == 'd' {
+                *cell = ' ';
+                monsters.push(Monster::new(x as f32 + 0.5, y as f32 + 0.5, Rc::clone(sheet)));
+            }
+        }
+    }
+    monsters
+}
+
+// La hoja del demonio desde los recursos, o dibujada aquí si no está
+pub fn demon_sheet(assets: &mut Assets) -> Rc<SpriteSheet> {
+    match SpriteSheet::load(assets, SHEET_FILE) {
+        Ok(sheet) => Rc::new(sheet),
+        Err(e) => {
+            println!("{}; using the built-in demon sprites.", e);
+            Rc::new(built_in_demon())
+        }
+    }
+}
+
+// Demonio de cuernos con los cuatro estados y ocho direcciones
+fn built_in_demon() -> SpriteSheet {
+    let mut states = HashMap::new();
+    let state = |frames: usize, directions: usize, frame_time: f32, looping: bool, pose: &dyn Fn(usize, f32) -> Pose| {
+        let rows = (0..directions)
+            .map(|direction| {
+                let view = direction as f32 / DIRECTIONS as f32 * TAU;
+                (0..frames).map(|frame| demon(view, pose(frame, frame as f32 / frames as f32))).collect()
+            })
+            .collect();
+        SheetState::new(rows, frame_time, looping)
+    };
+    states.insert("idle".to_string(), state(2, DIRECTIONS, 0.5, true, &|frame, _| Pose { bob: frame as f32 * 0.01, ..Pose::default() }));
+    states.insert("walk".to_string(), state(4, DIRECTIONS, 0.15, true, &|_, t| Pose { stride: (t * TAU).sin(), ..Pose::default() }));
+    states.insert("attack".to_string(), state(3, DIRECTIONS, 0.12, true, &|_, t| Pose { arms: (t * PI).sin() + 0.2, ..Pose::default() }));
+    states.insert("die".to_string(), state(5, 1, 0.15, false, &|frame, _| Pose { squash: frame as f32 / 4.0, ..Pose::default() }));
+    SpriteSheet::from_states(states)
+}
+
+#[derive(Default)]
+struct Pose {
+    bob: f32,    // Subida del cuerpo al respirar
+    stride: f32, // -1..1, piernas adelantadas al caminar
+    arms: f32,   // 0..1, brazos levantados para atacar
+    squash: f32, // 0..1, cuánto se ha desplomado al morir
+}
+
+// `view` es el ángulo desde el que se ve: 0 de frente, PI de espaldas
+fn demon(view: f32, pose: Pose) -> Texture {
+    let front = view.cos();
+    let turn = view.sin();
+    let width = 0.6 + 0.4 * front.abs(); // De lado se ve más estrecho
+    let (skin, dark, eye) = (0x8A1A12, 0x4A0A08, 0xFFD23A);
+
+    draw(|u, v| {
+        // Al morir se aplasta contra el suelo
+        let v = if pose.squash > 0.0 { 1.0 - (1.0 - v) / (1.0 - pose.squash * 0.85) } else { v + pose.bob };
+        if !(0.0..=1.0).contains(&v) {
+            return None;
+        }
+        let x = (u - 0.5) / width;
+
+        let head = (x * x + (v - 0.25) * (v - 0.25) * 1.2).sqrt() < 0.11;
+        let torso = (x / 0.2).powi(2) + ((v - 0.55) / 0.22).powi(2) < 1.0;
+        let horn = |side: f32| {
+            let hx = x - side * 0.09 - turn * 0.03;
+            v > 0.06 && v < 0.17 && hx.abs() < (v - 0.06) * 0.35
+        };
+        let leg = |side: f32| {
+            let lift = pose.stride * side * 0.06;
+            (x - side * 0.08 - lift).abs() < 0.04 && v > 0.72 && v < 0.97 - lift.abs()
+        };
+        let arm = |side: f32| {
+            let reach = pose.arms * 0.25;
+            (x - side * 0.22).abs() < 0.035 && v > 0.4 - reach && v < 0.65 - reach
+        };
+
+        if front > 0.2 && head {
+            // Ojos, desplazados hacia el lado al que mira
+            for side in [-1.0, 1.0] {
+                let ex = x - side * 0.045 - turn * 0.04;
+                if (ex * ex + (v - 0.24).powi(2)).sqrt() < 0.022 {
+                    return Some(eye);
+                }
+            }
+        }
+        if horn(-1.0) || horn(1.0) {
+            Some(0xD8C8A0)
+        } else if head || torso {
+            Some(if front < -0.2 { dark } else { skin })
+        } else if leg(-1.0) || leg(1.0) || arm(-1.0) || arm(1.0) {
+            Some(dark)
+        } else {
+            None
+        }
+    })
+}
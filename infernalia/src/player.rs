@@ -1,6 +1,10 @@
 use nalgebra_glm::Vec2;
 use std::f32::consts::PI;
 use std::time::Instant;
+use crate::map::{floor_speed, is_key};
+use crate::shape::cell_shape;
+use crate::world::World;
+use crate::lantern::Lantern;
 
 pub struct Player {
     pub pos: Vec2,  // Posición del jugador en el mundo
@@ -11,6 +15,9 @@ pub struct Player {
     pub collision_radius: f32, // Radio de colisión
     pub sensitivity: f32, // Sensibilidad del ratón
     pub last_update: Instant, // Última vez que se actualizó el jugador
+    pub lantern: Lantern, // Farol para el modo oscuridad
+    pub keys: Vec<char>, // Llaves recogidas ('r', 'b', 'y')
+    pub treasures: usize, // Tesoros encontrados en las salas secretas
 }
 
 impl Player {
@@ -24,6 +31,9 @@ impl Player {
             collision_radius: 0.25, // Radio de colisión
             sensitivity, // Sensibilidad del ratón
             last_update: Instant::now(), // Inicializar el tiempo
+            lantern: Lantern::new(),
+            keys: Vec::new(),
+            treasures: 0,
         }
     }
 
@@ -35,15 +45,15 @@ impl Player {
     }
 
     // Método para mover al jugador hacia adelante o atrás con detección de colisiones
-    pub fn move_forward(&mut self, direction: f32, maze: &Vec<Vec<char>>) {
+    pub fn move_forward(&mut self, direction: f32, maze: &[Vec<char>], world: &World) {
         let delta = self.delta_time();
-        let distance = self.speed * direction * delta;
+        let distance = self.speed * direction * delta * self.floor_speed(maze);
         
         let new_x = self.pos.x + distance * self.a.cos();
         let new_y = self.pos.y + distance * self.a.sin();
 
         // Verificar si la nueva posición está dentro de una pared
-        if !self.is_collision(new_x, new_y, maze) {
+        if !self.is_collision(new_x, new_y, maze, world) {
             self.pos.x = new_x;
             self.pos.y = new_y;
         }
@@ -52,15 +62,15 @@ impl Player {
     }
 
     // Método para hacer strafe del jugador a la izquierda o derecha con detección de colisiones
-    pub fn strafe(&mut self, direction: f32, maze: &Vec<Vec<char>>) {
+    pub fn strafe(&mut self, direction: f32, maze: &[Vec<char>], world: &World) {
         let delta = self.delta_time();
-        let distance = self.speed * direction * delta;
+        let distance = self.speed * direction * delta * self.floor_speed(maze);
 
         let new_x = self.pos.x + distance * self.a.sin();
         let new_y = self.pos.y - distance * self.a.cos();
 
         // Verificar si la nueva posición está dentro de una pared
-        if !self.is_collision(new_x, new_y, maze) {
+        if !self.is_collision(new_x, new_y, maze, world) {
             self.pos.x = new_x;
             self.pos.y = new_y;
         }
@@ -68,8 +78,13 @@ impl Player {
         self.last_update = Instant::now(); // Actualizar el tiempo
     }
 
+    // El fango y el hielo del suelo que pisa cambian su velocidad
+    fn floor_speed(&self, maze: &[Vec<char>]) -> f32 {
+        maze.get(self.pos.y as usize).and_then(|row| row.get(self.pos.x as usize)).map_or(1.0, |&cell| floor_speed(cell))
+    }
+
     // Método para verificar colisiones con el laberinto
-    fn is_collision(&self, x: f32, y: f32, maze: &Vec<Vec<char>>) -> bool {
+    fn is_collision(&self, x: f32, y: f32, maze: &[Vec<char>], world: &World) -> bool {
         let left = (x - self.collision_radius).floor() as isize;
         let right = (x + self.collision_radius).floor() as isize;
         let top = (y - self.collision_radius).floor() as isize;
@@ -80,10 +95,23 @@ impl Player {
             return true; // Considera cualquier salida fuera de los límites como una colisión
         }
     
-        if maze[top as usize][left as usize] != ' ' || maze[top as usize][right as usize] != ' ' ||
-            maze[bottom as usize][left as usize] != ' ' || maze[bottom as usize][right as usize] != ' ' {
+        let blocks = |x: isize, y: isize| world.blocks(maze, x as usize, y as usize);
+
+        if blocks(left, top) || blocks(right, top) || blocks(left, bottom) || blocks(right, bottom) {
             return true;
         }
+
+        // Columnas y muros finos: choque contra la figura exacta
+        for cell_y in top..=bottom {
+            for cell_x in left..=right {
+                let (cell_x, cell_y) = (cell_x as usize, cell_y as usize);
+                if let Some(shape) = cell_shape(maze[cell_y][cell_x], cell_x, cell_y) {
+                    if shape.distance_to(x, y) < self.collision_radius {
+                        return true;
+                    }
+                }
+            }
+        }
     
         false
     }
@@ -104,6 +132,58 @@ impl Player {
     }
 
 
+    // Celdas que el jugador tiene delante, de la más cercana a la más lejana, hasta `reach`
+    pub fn facing_cells(&self, reach: f32) -> Vec<(usize, usize)> {
+        let steps = (reach / 0.25).ceil() as usize;
+        let mut cells: Vec<(usize, usize)> = Vec::new();
+        for step in 1..=steps {
+            let distance = reach * step as f32 / steps as f32;
+            let x = (self.pos.x + self.a.cos() * distance).floor();
+            let y = (self.pos.y + self.a.sin() * distance).floor();
+            if x < 0.0 || y < 0.0 {
+                break;
+            }
+            let cell = (x as usize, y as usize);
+            if cells.last() != Some(&cell) {
+                cells.push(cell);
+            }
+        }
+        cells
+    }
+
+    // Recoge el frasco de aceite de la celda actual, si lo hay, y rellena el farol
+    pub fn collect_fuel(&mut self, maze: &mut [Vec<char>]) -> bool {
+        let (x, y) = (self.pos.x as usize, self.pos.y as usize);
+        if maze.get(y).and_then(|row| row.get(x)) == Some(&'o') {
+            maze[y][x] = ' ';
+            self.lantern.refill(30.0);
+            return true;
+        }
+        false
+    }
+
+    // Recoge la llave de la celda actual, si la hay
+    pub fn collect_key(&mut self, maze: &mut [Vec<char>]) -> Option<char> {
+        let (x, y) = (self.pos.x as usize, self.pos.y as usize);
+        let cell = *maze.get(y)?.get(x)?;
+        if is_key(cell) {
+            maze[y][x] = ' ';
+            self.keys.push(cell);
+            return Some(cell);
+        }
+        None
+    }
+
+    pub fn collect_treasure(&mut self, maze: &mut [Vec<char>]) -> bool {
+        let (x, y) = (self.pos.x as usize, self.pos.y as usize);
+        if maze.get(y).and_then(|row| row.get(x)) == Some(&'$') {
+            maze[y][x] = ' ';
+            self.treasures += 1;
+            return true;
+        }
+        false
+    }
+
     pub fn is_out_of_bounds(&self, maze: &Vec<Vec<char>>) -> bool {
         self.pos.x < 0.0 || self.pos.y < 0.0 || self.pos.x as usize >= maze[0].len() || self.pos.y as usize >= maze.len()
     }
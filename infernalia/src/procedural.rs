@@ -0,0 +1,270 @@
+use crate::color;
+use crate::texture::Texture;
+
+// Texturas generadas en código a partir de una semilla: lava, ladrillos de azufre,
+// piedra agrietada, muros de huesos, fango, hielo y el cielo. Todas se repiten sin costuras, así que
+// sirven de pared o suelo igual que las imágenes de `assets/textures`, y se pueden
+// pedir de cualquier tamaño (mejor potencias de dos, por los mipmaps).
+
+// Ruido de valor: números al azar en los vértices de una rejilla, interpolados con
+// suavidad. La rejilla se repite cada `period` celdas para que la textura encaje en sus bordes.
+pub struct Noise {
+    seed: u32,
+    period: u32,
+}
+
+impl Noise {
+    pub fn new(seed: u32, period: u32) -> Self {
+        Noise { seed, period: period.max(1) }
+    }
+
+    // Valor de 0.0 a 1.0 en (u, v), con u y v de 0.0 a 1.0 sobre la textura y `frequency`
+    // veces la rejilla base
+    pub fn value(&self, u: f32, v: f32, frequency: u32) -> f32 {
+        let period = self.period * frequency;
+        let (x, y) = (u * period as f32, v * period as f32);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (smooth(x - x0), smooth(y - y0));
+        let corner = |dx: i32, dy: i32| {
+            let cx = (x0 as i32 + dx).rem_euclid(period as i32) as u32;
+            let cy = (y0 as i32 + dy).rem_euclid(period as i32) as u32;
+            unit(hash(cx, cy, self.seed.wrapping_add(frequency)))
+        };
+        let top = mix(corner(0, 0), corner(1, 0), fx);
+        let bottom = mix(corner(0, 1), corner(1, 1), fx);
+        mix(top, bottom, fy)
+    }
+
+    // Suma de `octaves` capas, cada una con el doble de detalle y la mitad de peso
+    pub fn fbm(&self, u: f32, v: f32, octaves: u32) -> f32 {
+        let (mut sum, mut weight, mut total) = (0.0, 0.5, 0.0);
+        for octave in 0..octaves {
+            sum += self.value(u, v, 1 << octave) * weight;
+            total += weight;
+            weight *= 0.5;
+        }
+        sum / total
+    }
+
+    // Distancias al punto más cercano y al segundo de una rejilla de `period` x `period`
+    // celdas con un punto al azar en cada una (ruido celular). Donde casi coinciden
+    // está la frontera entre dos celdas: ahí van las grietas.
+    pub fn cells(&self, u: f32, v: f32) -> (f32, f32) {
+        let period = self.period as i32;
+        let (x, y) = (u * period as f32, v * period as f32);
+        let (cell_x, cell_y) = (x.floor() as i32, y.floor() as i32);
+        let (mut nearest, mut second) = (f32::MAX, f32::MAX);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (cell_x + dx, cell_y + dy);
+                let key = hash(nx.rem_euclid(period) as u32, ny.rem_euclid(period) as u32, self.seed ^ 0x5EED);
+                let px = nx as f32 + unit(key);
+                let py = ny as f32 + unit(hash(key, 7, self.seed));
+                let distance = ((px - x).powi(2) + (py - y).powi(2)).sqrt();
+                if distance < nearest {
+                    second = nearest;
+                    nearest = distance;
+                } else if distance < second {
+                    second = distance;
+                }
+            }
+        }
+        (nearest, second)
+    }
+}
+
+fn hash(x: u32, y: u32, seed: u32) -> u32 {
+    let mut h = x.wrapping_mul(0x8DA6B343) ^ y.wrapping_mul(0xD8163841) ^ seed.wrapping_mul(0xCB1AB31F);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5BD1E995);
+    h ^ (h >> 15)
+}
+
+fn unit(h: u32) -> f32 {
+    (h & 0xFFFF) as f32 / 65535.0
+}
+
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+// Color de una rampa de colores repartidos por igual entre 0.0 y 1.0
+fn ramp(stops: &[u32], t: f32) -> u32 {
+    let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = (position as usize).min(stops.len() - 2);
+    color::lerp(stops[index], stops[index + 1], color::to_fixed(position - index as f32))
+}
+
+fn generate(width: usize, height: usize, texel: impl Fn(f32, f32) -> u32) -> Texture {
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let (u, v) = (x as f32 / width as f32, y as f32 / height as f32);
+            data.push(0xFF000000 | texel(u, v));
+        }
+    }
+    Texture::new(width, height, data)
+}
+
+// Roca fundida: ruido deformado por otro ruido, con vetas amarillas donde más calienta
+pub fn lava(size: usize, seed: u32) -> Texture {
+    let noise = Noise::new(seed, 4);
+    let warp = Noise::new(seed.wrapping_add(1), 3);
+    generate(size, size, |u, v| {
+        let (wu, wv) = (warp.fbm(u, v, 3) * 0.35, warp.fbm(v, u, 3) * 0.35);
+        let heat = noise.fbm(u + wu, v + wv, 4);
+        let veins = 1.0 - ((heat - 0.5).abs() * 6.0).min(1.0);
+        ramp(&[0x2A0400, 0x8A1000, 0xE04A08, 0xFFB020, 0xFFF0A0], heat * 0.7 + veins * 0.35)
+    })
+}
+
+// Ladrillos de piedra rojiza con costras amarillas de azufre, en hiladas alternas
+pub fn brimstone_bricks(size: usize, seed: u32) -> Texture {
+    let grime = Noise::new(seed, 8);
+    let rows = 8;
+    generate(size, size, |u, v| {
+        let row = (v * rows as f32) as u32;
+        let offset = if row.is_multiple_of(2) { 0.0 } else { 0.5 / (rows / 2) as f32 };
+        let columns = rows / 2;
+        let brick_u = ((u + offset) * columns as f32).fract();
+        let brick_v = (v * rows as f32).fract();
+        let column = (((u + offset) * columns as f32) as u32) % columns;
+
+        let dirt = grime.fbm(u, v, 4);
+        if brick_u < 0.06 || brick_v < 0.1 {
+            return ramp(&[0x120806, 0x2A1A10], dirt); // Mortero
+        }
+        let tone = unit(hash(column, row, seed));
+        let base = ramp(&[0x4A1410, 0x6E2418, 0x8A3A1E], tone * 0.6 + dirt * 0.4);
+        // Azufre acumulado en la parte baja de algunos ladrillos
+        let sulfur = grime.value(u, v, 4) * brick_v;
+        if sulfur > 0.55 {
+            color::lerp(base, 0xC8B030, color::to_fixed((sulfur - 0.55) * 4.0))
+        } else {
+            base
+        }
+    })
+}
+
+// Losas de piedra gris separadas por grietas oscuras
+pub fn cracked_stone(size: usize, seed: u32) -> Texture {
+    let cracks = Noise::new(seed, 6);
+    let grain = Noise::new(seed.wrapping_add(2), 16);
+    generate(size, size, |u, v| {
+        let (nearest, second) = cracks.cells(u, v);
+        let edge = second - nearest;
+        let stone = ramp(&[0x2E2A28, 0x4A4440, 0x5E5650], grain.fbm(u, v, 3));
+        if edge < 0.05 {
+            0x0E0A0A
+        } else if edge < 0.12 {
+            color::scale(stone, color::to_fixed(0.4 + edge * 5.0))
+        } else {
+            stone
+        }
+    })
+}
+
+// Muro de huesos apilados: tibias tumbadas en filas y alguna calavera entre ellas
+pub fn bone_wall(size: usize, seed: u32) -> Texture {
+    let stain = Noise::new(seed, 8);
+    let rows = 6;
+    generate(size, size, |u, v| {
+        let row = (v * rows as f32) as u32;
+        let shift = unit(hash(row, 0, seed));
+        let bones = 3;
+        let along = ((u + shift) * bones as f32).fract();
+        let bone = (((u + shift) * bones as f32) as u32) % bones;
+        let across = (v * rows as f32).fract() - 0.5;
+
+        let ivory = ramp(&[0x8A7A58, 0xC8B890, 0xE8DCC0], 1.0 - stain.fbm(u, v, 4));
+        let gap = 0x120C08;
+
+        if hash(bone, row, seed).is_multiple_of(5) {
+            // Calavera: cráneo redondo con dos cuencas oscuras
+            let (dx, dy) = ((along - 0.5) * 1.6, across);
+            if dx * dx + dy * dy > 0.2 {
+                return gap;
+            }
+            let socket = |side: f32| (dx - side * 0.17).powi(2) + (dy + 0.05).powi(2) < 0.012;
+            return if socket(-1.0) || socket(1.0) { gap } else { ivory };
+        }
+
+        // Tibia: una barra con los extremos abultados
+        let knob = |end: f32| ((along - end) * 3.0).powi(2) + across.powi(2) < 0.09;
+        if (across.abs() < 0.16 && (0.1..0.9).contains(&along)) || knob(0.12) || knob(0.88) {
+            ivory
+        } else {
+            gap
+        }
+    })
+}
+
+// Fango del pantano: barro oscuro con burbujas y charcos de agua sucia
+pub fn mud(size: usize, seed: u32) -> Texture {
+    let murk = Noise::new(seed, 5);
+    let bubbles = Noise::new(seed.wrapping_add(3), 10);
+    generate(size, size, |u, v| {
+        let depth = murk.fbm(u, v, 4);
+        let (nearest, _) = bubbles.cells(u, v);
+        let base = ramp(&[0x140E08, 0x2E2210, 0x46381C, 0x3A4A2A], depth);
+        if nearest < 0.12 {
+            color::lerp(base, 0x6A6040, color::to_fixed(1.0 - nearest / 0.12))
+        } else {
+            base
+        }
+    })
+}
+
+// Hielo del Cocito: azul pálido con grietas blancas
+pub fn ice(size: usize, seed: u32) -> Texture {
+    let frost = Noise::new(seed, 4);
+    let cracks = Noise::new(seed.wrapping_add(4), 5);
+    generate(size, size, |u, v| {
+        let (nearest, second) = cracks.cells(u, v);
+        let base = ramp(&[0x1A3A5A, 0x4A7AA0, 0x9AC8E8, 0xDDF0FF], frost.fbm(u, v, 5));
+        if second - nearest < 0.04 {
+            color::lerp(base, 0xF0F8FF, 200)
+        } else {
+            base
+        }
+    })
+}
+
+// Cielo del infierno: nubes de humo sobre un resplandor rojo
+pub fn hell_sky(width: usize, height: usize, seed: u32) -> Texture {
+    let clouds = Noise::new(seed, 4);
+    generate(width, height, |u, v| {
+        let smoke = clouds.fbm(u, v, 5);
+        ramp(&[0x1A0404, 0x4A0A06, 0x8A2208, 0xC8501A], smoke)
+    })
+}
+
+// Textura generada por su nombre (el mismo que el de su función), para elegirlas desde
+// archivos de datos como `campaign.ron`; "wall" es la pared elegida por la semilla
+pub fn by_name(name: &str, width: usize, height: usize, seed: u32) -> Option<Texture> {
+    let size = width.max(height);
+    match name {
+        "lava" => Some(lava(size, seed)),
+        "brimstone_bricks" => Some(brimstone_bricks(size, seed)),
+        "cracked_stone" => Some(cracked_stone(size, seed)),
+        "bone_wall" => Some(bone_wall(size, seed)),
+        "mud" => Some(mud(size, seed)),
+        "ice" => Some(ice(size, seed)),
+        "hell_sky" => Some(hell_sky(width, height, seed)),
+        "wall" => Some(wall(size, seed)),
+        _ => None,
+    }
+}
+
+// Una de las paredes generadas, elegida por la semilla, para que cada laberinto tenga su aspecto
+pub fn wall(size: usize, seed: u32) -> Texture {
+    match seed % 3 {
+        0 => brimstone_bricks(size, seed),
+        1 => cracked_stone(size, seed),
+        _ => bone_wall(size, seed),
+    }
+}
@@ -0,0 +1,113 @@
+use crate::atlas::Atlas;
+use crate::map::{is_key, key_color, key_name};
+use crate::sprite::{AnimatedSprite, Sprite};
+use crate::texture::Texture;
+
+// Objetos del mapa que se ven como sprites en la vista 3D: antorchas, braseros,
+// llaves, aceite y tesoros. Las imágenes se generan aquí, con alfa 0 alrededor, salvo
+// las que vienen en el atlas ("torch", "brazier", "oil", "treasure", "key_red"...).
+
+const SIZE: usize = 64;
+
+pub fn draw(shape: impl Fn(f32, f32) -> Option<u32>) -> Texture {
+    let mut data = Vec::with_capacity(SIZE * SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let (u, v) = ((x as f32 + 0.5) / SIZE as f32, (y as f32 + 0.5) / SIZE as f32);
+            data.push(shape(u, v).map_or(0, |color| 0xFF000000 | color));
+        }
+    }
+    Texture::new(SIZE, SIZE, data)
+}
+
+// Llama en forma de gota con la punta en (0.5, top) y la base en `bottom`; `sway` la inclina
+fn flame(u: f32, v: f32, top: f32, bottom: f32, sway: f32) -> Option<u32> {
+    if v < top || v > bottom {
+        return None;
+    }
+    let t = (v - top) / (bottom - top); // 0 en la punta, 1 en la base
+    let center = 0.5 + sway * (1.0 - t);
+    let half_width = 0.14 * (t * std::f32::consts::PI * 0.9).sin().max(0.0);
+    let across = (u - center).abs() / half_width.max(0.001);
+    if across > 1.0 {
+        return None;
+    }
+    // Núcleo amarillo y bordes rojizos
+    Some(if across < 0.45 && t > 0.35 { 0xFFE27A } else if across < 0.75 { 0xFF9A1E } else { 0xD8400E })
+}
+
+fn flame_frames(body: impl Fn(f32, f32) -> Option<u32>, top: f32, bottom: f32) -> Vec<Texture> {
+    [-0.04, 0.0, 0.05, 0.01]
+        .iter()
+        .map(|&sway| draw(|u, v| flame(u, v, top, bottom, sway).or_else(|| body(u, v))))
+        .collect()
+}
+
+fn torch() -> AnimatedSprite {
+    let stick = |u: f32, v: f32| ((u - 0.5).abs() < 0.04 && v > 0.45).then_some(0x4A2C14);
+    AnimatedSprite::new(flame_frames(stick, 0.1, 0.5), 0.12)
+}
+
+fn brazier() -> AnimatedSprite {
+    let bowl = |u: f32, v: f32| {
+        let cup = v > 0.5 && v < 0.65 && (u - 0.5).abs() < 0.3 - (v - 0.5);
+        let legs = v >= 0.65 && ((u - 0.32).abs() < 0.03 || (u - 0.68).abs() < 0.03);
+        (cup || legs).then_some(0x2C2A2E)
+    };
+    AnimatedSprite::new(flame_frames(bowl, 0.05, 0.55), 0.1)
+}
+
+fn key(color: u32) -> Texture {
+    draw(|u, v| {
+        let (dx, dy) = (u - 0.5, v - 0.25);
+        let ring = ((dx * dx + dy * dy).sqrt() - 0.16).abs() < 0.05;
+        let shaft = (u - 0.5).abs() < 0.05 && (0.4..0.95).contains(&v);
+        let teeth = (0.55..0.65).contains(&u) && ((0.7..0.76).contains(&v) || (0.84..0.9).contains(&v));
+        (ring || shaft || teeth).then_some(color)
+    })
+}
+
+fn oil_flask() -> Texture {
+    draw(|u, v| {
+        let neck = (u - 0.5).abs() < 0.08 && (0.1..0.4).contains(&v);
+        let (dx, dy) = (u - 0.5, v - 0.68);
+        let belly = (dx * dx + dy * dy).sqrt() < 0.3;
+        if neck && v < 0.18 {
+            Some(0x6A4A2A) // Tapón de corcho
+        } else if belly && v > 0.62 {
+            Some(0xE0A030) // Aceite
+        } else if neck || belly {
+            Some(0x8AA0A8) // Cristal
+        } else {
+            None
+        }
+    })
+}
+
+fn treasure() -> Texture {
+    draw(|u, v| {
+        // Montón de monedas: una campana baja con brillos
+        let heap = v > 0.55 && (u - 0.5).abs() < 0.45 * ((v - 0.55) / 0.45).sqrt();
+        heap.then(|| if ((u * 40.0).sin() * (v * 33.0).cos()) > 0.7 { 0xFFF5B0 } else { 0xE8B820 })
+    })
+}
+
+// Sprites de todos los objetos del laberinto
+pub fn sprites_from_maze(maze: &[Vec<char>], atlas: &Atlas) -> Vec<Sprite> {
+    let mut sprites = Vec::new();
+    for (y, row) in maze.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            let (scale, name, generate): (f32, String, fn(char) -> AnimatedSprite) = match cell {
+                't' => (0.7, "torch".to_string(), |_| torch()),
+                'u' => (0.8, "brazier".to_string(), |_| brazier()),
+                'o' => (0.3, "oil".to_string(), |_| AnimatedSprite::new(vec![oil_flask()], 1.0)),
+                '$' => (0.35, "treasure".to_string(), |_| AnimatedSprite::new(vec![treasure()], 1.0)),
+                c if is_key(c) => (0.3, format!("key_{}", key_name(c)), |c| AnimatedSprite::new(vec![key(key_color(c))], 1.0)),
+                _ => continue,
+            };
+            let animation = atlas.sprite(&name).unwrap_or_else(|| generate(cell));
+            sprites.push(Sprite::new(x as f32 + 0.5, y as f32 + 0.5, scale, animation, cell));
+        }
+    }
+    sprites
+}
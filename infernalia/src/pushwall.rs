@@ -0,0 +1,126 @@
+use crate::map::is_push_wall;
+use crate::player::Player;
+
+// Paredes secretas al estilo Wolfenstein: parecen paredes normales, pero al
+// usarlas se deslizan dos celdas hacia atrás y dejan al descubierto lo que ocultaban.
+
+const SLIDE_SPEED: f32 = 1.0;  // Celdas por segundo
+const SLIDE_CELLS: usize = 2;
+const USE_DISTANCE: f32 = 1.5;
+
+pub struct PushWall {
+    pub origin: (usize, usize),
+    pub direction: (isize, isize),
+    pub moved: f32, // Celdas recorridas (0.0..=2.0)
+}
+
+impl PushWall {
+    // Esquina superior izquierda del bloque en su posición actual
+    pub fn position(&self) -> (f32, f32) {
+        (
+            self.origin.0 as f32 + self.direction.0 as f32 * self.moved,
+            self.origin.1 as f32 + self.direction.1 as f32 * self.moved,
+        )
+    }
+
+    fn cell(&self, step: usize) -> (usize, usize) {
+        (
+            (self.origin.0 as isize + self.direction.0 * step as isize) as usize,
+            (self.origin.1 as isize + self.direction.1 * step as isize) as usize,
+        )
+    }
+
+    // Corta un rayo contra el bloque (intersección rayo-caja); devuelve la distancia y la cara golpeada
+    pub fn intersect(&self, origin_x: f32, origin_y: f32, cos_angle: f32, sin_angle: f32) -> Option<(f32, usize)> {
+        let (bx, by) = self.position();
+        let slab = |origin: f32, direction: f32, min: f32| {
+            if direction == 0.0 {
+                if origin >= min && origin <= min + 1.0 { (f32::NEG_INFINITY, f32::INFINITY) } else { (f32::INFINITY, f32::NEG_INFINITY) }
+            } else {
+                let (t0, t1) = ((min - origin) / direction, (min + 1.0 - origin) / direction);
+                (t0.min(t1), t0.max(t1))
+            }
+        };
+
+        let (x_near, x_far) = slab(origin_x, cos_angle, bx);
+        let (y_near, y_far) = slab(origin_y, sin_angle, by);
+        let near = x_near.max(y_near);
+        let far = x_far.min(y_far);
+
+        if near > far || near <= 0.0 {
+            return None;
+        }
+        Some((near, if x_near > y_near { 0 } else { 1 }))
+    }
+}
+
+pub struct PushWalls {
+    moving: Vec<PushWall>,
+}
+
+impl PushWalls {
+    pub fn new() -> Self {
+        PushWalls { moving: Vec::new() }
+    }
+
+    // Bloque en movimiento que ocupa (aunque sea en parte) la celda (x, y)
+    pub fn moving_at(&self, x: usize, y: usize) -> Option<&PushWall> {
+        self.moving.iter().find(|wall| (0..=SLIDE_CELLS).any(|step| wall.cell(step) == (x, y)))
+    }
+
+    // Empuja la pared secreta que el jugador tiene delante en la dirección en la que mira,
+    // siempre que haya dos celdas libres detrás
+    pub fn try_push(&mut self, player: &Player, maze: &mut [Vec<char>]) -> bool {
+        let Some((x, y)) = player.facing_cells(USE_DISTANCE).into_iter().find(|&(x, y)| is_push_wall(maze[y][x])) else {
+            return false;
+        };
+        if self.moving_at(x, y).is_some() {
+            return false;
+        }
+
+        let (cos, sin) = (player.a.cos(), player.a.sin());
+        let direction = if cos.abs() > sin.abs() { (cos.signum() as isize, 0) } else { (0, sin.signum() as isize) };
+        let wall = PushWall { origin: (x, y), direction, moved: 0.0 };
+
+        let path_is_free = (1..=SLIDE_CELLS).all(|step| {
+            let (cx, cy) = wall.cell(step);
+            maze.get(cy).and_then(|row| row.get(cx)) == Some(&' ')
+        });
+        if !path_is_free {
+            println!("La pared no se mueve.");
+            return false;
+        }
+
+        // Las celdas por las que pasará quedan ocupadas hasta que el bloque las deje atrás
+        for step in 1..=SLIDE_CELLS {
+            let (cx, cy) = wall.cell(step);
+            maze[cy][cx] = '*';
+        }
+        self.moving.push(wall);
+        true
+    }
+
+    // Avanza los bloques; devuelve true si alguno terminó de moverse y el mapa cambió
+    pub fn update(&mut self, delta_time: f32, maze: &mut [Vec<char>]) -> bool {
+        let mut finished = false;
+
+        for wall in self.moving.iter_mut() {
+            wall.moved = (wall.moved + SLIDE_SPEED * delta_time).min(SLIDE_CELLS as f32);
+
+            // Liberar las celdas que el bloque ya ha dejado atrás por completo
+            for step in 0..wall.moved.floor() as usize {
+                let (cx, cy) = wall.cell(step);
+                maze[cy][cx] = ' ';
+            }
+
+            if wall.moved >= SLIDE_CELLS as f32 {
+                let (cx, cy) = wall.cell(SLIDE_CELLS);
+                maze[cy][cx] = '#';
+                finished = true;
+            }
+        }
+
+        self.moving.retain(|wall| wall.moved < SLIDE_CELLS as f32);
+        finished
+    }
+}
@@ -1,58 +1,224 @@
 use crate::framebuffer::Framebuffer;
 use crate::player::Player;
 use crate::texture::Texture;
+use crate::map::{is_door, is_mirror, is_portal, is_push_wall, is_see_through, is_wall, wall_height, MAX_WALL_HEIGHT};
+use crate::shape::cell_shape;
+use crate::world::World;
 use std::f32::consts::PI;
 
+// Portales que puede atravesar un mismo rayo; evita bucles infinitos entre portales enfrentados
+const MAX_PORTAL_JUMPS: usize = 4;
+
 pub struct Intersect {
     pub distance: f32,
     pub impact: char,
     pub hit_x: f32,  // Coordenada x del impacto en el mundo
     pub hit_y: f32,  // Coordenada y del impacto en el mundo
+    pub side: usize, // 0: cara vertical (se cruzó una línea x), 1: cara horizontal
+    pub height: f32, // Altura de la pared golpeada
+    pub offset: f32, // Desplazamiento de la textura (hoja de una puerta corredera)
+    pub angle: f32,  // Dirección del rayo al llegar (cambia al cruzar un portal o reflejarse)
+    pub reflections: u32, // Espejos en los que rebotó el rayo antes de llegar aquí
+    pub surface: Option<f32>, // Posición en la textura de una columna o muro fino (0.0..1.0)
 }
 
+impl Intersect {
+    // Posición horizontal del impacto dentro de la pared (0.0..1.0) para mapear la textura
+    pub fn wall_x(&self) -> f32 {
+        if let Some(surface) = self.surface {
+            return surface;
+        }
+        let along = if self.side == 0 {
+            self.hit_y - self.hit_y.floor()
+        } else {
+            self.hit_x - self.hit_x.floor()
+        };
+        along - self.offset
+    }
+}
+
+// Recorre la cuadrícula celda por celda (DDA), así el punto de impacto y la cara
+// golpeada son exactos. El rayo sigue más allá de las paredes bajas mientras una
+// pared más lejana pueda asomar por encima, y devuelve los impactos visibles
+// ordenados de la más cercana a la más lejana. Las paredes con huecos se
+// registran pero no tapan nada, así que el rayo continúa a través de ellas.
+// Las puertas se cortan contra su hoja, hundida en el centro de la celda, las
+// paredes secretas en movimiento contra su bloque desplazado y las columnas y
+// muros finos contra su figura. Al chocar con un
+// portal el rayo sigue desde el portal enlazado, y en un espejo rebota (hasta
+// `world.mirror_depth` veces), acumulando en ambos casos la distancia recorrida.
 pub fn cast_ray(
     framebuffer: &mut Framebuffer,
-    wall_texture: &Texture,
-    maze: &Vec<Vec<char>>,
+    _wall_texture: &Texture,
+    maze: &[Vec<char>],
+    world: &World,
     player: &Player,
     ray_angle: f32,
     draw_line: bool,
-) -> Intersect {
-    let mut distance = 0.0;
-    let mut hit = false;
-    let mut impact = ' ';
-    let (cos_angle, sin_angle) = (ray_angle.cos(), ray_angle.sin());
-
-    let mut hit_x = 0.0;
-    let mut hit_y = 0.0;
-
-    while !hit {
-        distance += 0.1;
-        let x = player.pos.x + cos_angle * distance;
-        let y = player.pos.y + sin_angle * distance;
-
-        if x as usize >= maze[0].len() || y as usize >= maze.len() {
-            break; // El rayo ha salido de los límites del laberinto
-        }
+) -> Vec<Intersect> {
+    let mut hits = Vec::new();
 
-        impact = maze[y as usize][x as usize];
-        if impact != ' ' {
-            hit = true;
-            hit_x = x;
-            hit_y = y;
-        }
+    // Con el ojo a media altura, lo que sobresale en pantalla una pared es (altura - 0.5) / distancia.
+    // Una pared solo se ve si asoma por encima de todas las anteriores.
+    let mut skyline = f32::NEG_INFINITY;
+
+    // Cada tramo del rayo empieza en el jugador o a la salida de un portal
+    let (mut origin_x, mut origin_y) = (player.pos.x, player.pos.y);
+    let mut angle = ray_angle;
+    let mut traveled = 0.0;
+    let mut jumps = 0;
+    let mut reflections = 0;
+
+    'segments: loop {
+        let (cos_angle, sin_angle) = (angle.cos(), angle.sin());
+
+        let mut map_x = origin_x.floor() as isize;
+        let mut map_y = origin_y.floor() as isize;
+
+        // Distancia que recorre el rayo para cruzar una celda completa en cada eje
+        let delta_x = if cos_angle == 0.0 { f32::INFINITY } else { (1.0 / cos_angle).abs() };
+        let delta_y = if sin_angle == 0.0 { f32::INFINITY } else { (1.0 / sin_angle).abs() };
+
+        let (step_x, mut side_dist_x) = if cos_angle < 0.0 {
+            (-1, (origin_x - map_x as f32) * delta_x)
+        } else {
+            (1, (map_x as f32 + 1.0 - origin_x) * delta_x)
+        };
+        let (step_y, mut side_dist_y) = if sin_angle < 0.0 {
+            (-1, (origin_y - map_y as f32) * delta_y)
+        } else {
+            (1, (map_y as f32 + 1.0 - origin_y) * delta_y)
+        };
+
+        let mut local: f32;
+        let mut side: usize;
+        let mut previous = ' ';
 
-        if draw_line {
-            framebuffer.point(x as usize, y as usize); // Opcional para dibujar la línea en el minimapa
+        loop {
+            if side_dist_x < side_dist_y {
+                local = side_dist_x;
+                side_dist_x += delta_x;
+                map_x += step_x;
+                side = 0;
+            } else {
+                local = side_dist_y;
+                side_dist_y += delta_y;
+                map_y += step_y;
+                side = 1;
+            }
+
+            if map_x < 0 || map_y < 0 || map_y as usize >= maze.len() || map_x as usize >= maze[map_y as usize].len() {
+                break 'segments; // El rayo ha salido de los límites del laberinto
+            }
+
+            // Ni siquiera la pared más alta podría asomar ya por encima de lo que tenemos delante
+            let distance = traveled + local;
+            if distance > 0.0 && (MAX_WALL_HEIGHT - 0.5) / distance <= skyline {
+                break 'segments;
+            }
+
+            if draw_line {
+                framebuffer.point(map_x as usize, map_y as usize); // Opcional para dibujar la línea en el minimapa
+            }
+
+            let impact = maze[map_y as usize][map_x as usize];
+            let entered_from = previous;
+            previous = impact;
+            if !is_wall(impact) {
+                continue;
+            }
+
+            // Atravesar el portal: el rayo reaparece en la cara libre del portal enlazado
+            if is_portal(impact) && jumps < MAX_PORTAL_JUMPS {
+                let normal = if side == 0 { (-step_x, 0) } else { (0, -step_y) };
+                let point = (origin_x + cos_angle * local, origin_y + sin_angle * local);
+                if let Some(((x, y), rotation)) = world.teleporters.warp(map_x as usize, map_y as usize, normal, point) {
+                    origin_x = x;
+                    origin_y = y;
+                    angle += rotation;
+                    traveled = distance;
+                    jumps += 1;
+                    continue 'segments;
+                }
+            }
+
+            // Reflejarse: la componente perpendicular a la cara cambia de signo
+            if is_mirror(impact) && reflections < world.mirror_depth {
+                origin_x += cos_angle * local;
+                origin_y += sin_angle * local;
+                angle = if side == 0 { PI - angle } else { -angle };
+                traveled = distance;
+                reflections += 1;
+                continue 'segments;
+            }
+
+            // El rayo atraviesa la celda de una puerta por el hueco que deja la hoja al abrirse
+            let moving_wall = if is_push_wall(impact) { world.push_walls.moving_at(map_x as usize, map_y as usize) } else { None };
+            let (local, side, offset, surface) = if let Some(shape) = cell_shape(impact, map_x as usize, map_y as usize) {
+                // Las paredes parciales solo tapan si el rayo corta su figura
+                match shape.intersect(origin_x, origin_y, cos_angle, sin_angle) {
+                    Some((shape_distance, along, shape_side)) => (shape_distance, shape_side, 0.0, Some(along)),
+                    None => continue,
+                }
+            } else if is_door(impact) {
+                let door = match world.doors.get(map_x as usize, map_y as usize) {
+                    Some(door) => door,
+                    None => continue,
+                };
+                match door.intersect(map_x as usize, map_y as usize, origin_x, origin_y, cos_angle, sin_angle) {
+                    Some((door_distance, _)) => (door_distance, if door.horizontal { 1 } else { 0 }, door.offset, None),
+                    None => continue,
+                }
+            } else if let Some(wall) = moving_wall {
+                // El bloque puede ocupar dos celdas; el impacto cuenta solo en la celda donde cae
+                match wall.intersect(origin_x, origin_y, cos_angle, sin_angle) {
+                    Some((wall_distance, wall_side)) => {
+                        let inside_x = (origin_x + cos_angle * (wall_distance + 0.001)).floor() as isize;
+                        let inside_y = (origin_y + sin_angle * (wall_distance + 0.001)).floor() as isize;
+                        if (inside_x, inside_y) != (map_x, map_y) {
+                            continue;
+                        }
+                        (wall_distance, wall_side, 0.0, None)
+                    }
+                    None => continue,
+                }
+            } else {
+                (local, side, 0.0, None)
+            };
+            let distance = traveled + local;
+
+            let height = wall_height(impact);
+            let profile = (height - 0.5) / distance.max(0.001);
+            let see_through = is_see_through(impact);
+
+            // De una hilera de celdas con huecos iguales solo se ve la primera cara
+            if profile > skyline && !(see_through && impact == entered_from) {
+                hits.push(Intersect {
+                    distance,
+                    impact,
+                    hit_x: origin_x + cos_angle * local,
+                    hit_y: origin_y + sin_angle * local,
+                    side,
+                    height,
+                    offset,
+                    angle,
+                    reflections,
+                    surface,
+                });
+            }
+
+            if see_through {
+                continue;
+            }
+            skyline = skyline.max(profile);
+
+            if height >= MAX_WALL_HEIGHT {
+                break 'segments;
+            }
         }
     }
 
-    Intersect {
-        distance,
-        impact,
-        hit_x,
-        hit_y,
-    }
+    hits
 }
 
 fn render_skybox(framebuffer: &mut Framebuffer, sky_texture: &Texture, player_angle: f32) {
@@ -63,7 +229,7 @@ fn render_skybox(framebuffer: &mut Framebuffer, sky_texture: &Texture, player_an
         let sky_x = (((player_angle + (x as f32 / framebuffer.width as f32) * 2.0 * PI) % (2.0 * PI)) / (2.0 * PI) * sky_width) as usize;
         for y in 0..(framebuffer.height / 2) {
             let sky_y = (y as f32 / (framebuffer.height as f32 / 2.0) * sky_height) as usize;
-            let color = sky_texture.data[sky_y * sky_texture.width + sky_x];
+            let color = sky_texture.get_pixel(sky_x, sky_y);
             framebuffer.set_current_color(color);
             framebuffer.point(x, y);
         }
@@ -90,7 +256,7 @@ fn render_floor(framebuffer: &mut Framebuffer, floor_texture: &Texture, player:
             let texture_x = (floor_x * floor_width as f32) as usize % floor_texture.width;
             let texture_y = (floor_y * floor_height as f32) as usize % floor_texture.height;
 
-            let color = floor_texture.data[texture_y * floor_texture.width + texture_x];
+            let color = floor_texture.get_pixel(texture_x, texture_y);
             framebuffer.set_current_color(color);
             framebuffer.point(x, y);
         }
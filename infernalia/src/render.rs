@@ -1,25 +1,77 @@
+use crate::animation::AnimatedTexture;
+use crate::atlas::Atlas;
 use crate::framebuffer::Framebuffer;
 use crate::player::Player;
-use crate::texture::Texture;
+use crate::texture::{Texture, Wrap};
 use crate::raycaster::cast_ray;
+use crate::fog::Fog;
+use crate::color;
+use crate::light::LightMap;
+use crate::map::{is_key, is_open, key_color, key_for_door, key_name};
+use crate::lantern::Lantern;
+use crate::transparent::is_transparent;
+use crate::wall_textures::WallTextures;
+use crate::decal::{is_mirrored, Face};
+use crate::settings::Settings;
+use crate::sprite::Billboard;
+use crate::world::World;
 use std::f32::consts::PI;
 
+const MIRROR_TINT: u32 = 0x8FA8C8;
+const MIRROR_TINT_STRENGTH: u32 = 56; // Por reflejo, en la escala de `color::ONE`
+
  fn render_floor(
     framebuffer: &mut Framebuffer,
     floor_texture: &Texture,
     player: &Player,  // Ajustamos para incluir al jugador
+    maze: &[Vec<char>],
+    fog: &Fog,
+    lighting: &LightMap,
+    wall_textures: &WallTextures,
 ) {
     let half_height = framebuffer.height as f32 / 2.0;
 
+    // Dirección de cada columna, con el mismo reparto de ángulos que los rayos de las paredes
+    // junto con cuánto le llega del cono del farol
+    let directions: Vec<(f32, f32, f32)> = (0..framebuffer.width)
+        .map(|x| {
+            let offset = -(player.fov / 2.0) + (player.fov * x as f32 / framebuffer.width as f32);
+            let a = player.a + offset;
+            (a.cos(), a.sin(), player.lantern.cone_factor(offset))
+        })
+        .collect();
+
     for y in (half_height as usize)..framebuffer.height {
-        for x in 0..framebuffer.width {
-            // Aquí calculamos las coordenadas de la textura basándonos en la posición del jugador
-            let texture_x = ((player.pos.x + x as f32) % floor_texture.width as f32) as usize;
-            let texture_y = ((player.pos.y + y as f32) % floor_texture.height as f32) as usize;
+        // Distancia a la que está el suelo visible en esta fila (misma proyección que las paredes)
+        let row_distance = half_height / (2.0 * (y as f32 - half_height + 1.0));
+        let fog_factor = fog.factor(row_distance);
+
+        for (x, &(dir_x, dir_y, cone_factor)) in directions.iter().enumerate() {
+            // Punto del suelo en el mundo que corresponde a este píxel
+            let floor_x = player.pos.x + dir_x * row_distance;
+            let floor_y = player.pos.y + dir_y * row_distance;
+            let (cell_x, cell_y) = (floor_x.floor() as usize, floor_y.floor() as usize);
 
             // Obtenemos el color de la textura en la posición calculada
-            let color = floor_texture.data[texture_y * floor_texture.width + texture_x];
-            framebuffer.set_current_color(color);
+            let mut color = floor_texture.sample(floor_x, floor_y, Wrap::Repeat, false);
+            match maze.get(cell_y).and_then(|row| row.get(cell_x)) {
+                Some(&pool @ ('~' | ';' | '=')) => {
+                    // Charco de lava que fluye, de fango o de hielo
+                    if let Some(texture) = wall_textures.get(pool) {
+                        color = color::lerp(color, texture.sample(floor_x, floor_y), 208);
+                    }
+                }
+                Some('o') => color = color::lerp(color, 0xFFC040, 96),  // Frasco de aceite
+                Some('$') => color = color::lerp(color, 0xFFD700, 128), // Tesoro
+                Some('@') => color = color::lerp(color, 0x9A30E0, 144), // Teletransportador
+                Some(&key) if is_key(key) => color = color::lerp(color, key_color(key), 160),
+                _ => {}
+            }
+
+            let mut light = lighting.light_at(cell_x, cell_y, floor_x, floor_y);
+            player.lantern.add_light(&mut light, row_distance, cone_factor);
+            let color = color::modulate(color, light);
+            framebuffer.set_current_color(fog.apply(color, fog_factor));
             framebuffer.point(x, y);
         }
     }
@@ -27,41 +79,59 @@ use std::f32::consts::PI;
 
 fn render_skybox(
     framebuffer: &mut Framebuffer,
-    sky_texture: &Texture,
+    sky: &AnimatedTexture,
     player: &Player,
+    brightness: u32, // En punto fijo; en el modo oscuridad el cielo apenas se ve
 ) {
     let half_height = framebuffer.height as f32 / 2.0;
-
+    // Las nubes se desplazan con el tiempo (`sample` ya suma el desplazamiento)
     for y in 0..(half_height as usize) {
         let row_distance = player.pos.y / (half_height - y as f32);
 
         for x in 0..framebuffer.width {
-            let sky_x = ((x as f32 / framebuffer.width as f32) * sky_texture.width as f32) as usize % sky_texture.width;
-            let sky_y = (row_distance * sky_texture.height as f32) as usize % sky_texture.height;
-
-            let color = sky_texture.data[sky_y * sky_texture.width + sky_x];
-            framebuffer.set_current_color(color);
+            let color = sky.sample(x as f32 / framebuffer.width as f32, row_distance);
+            framebuffer.set_current_color(color::scale(color, brightness));
             framebuffer.point(x, y);
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render3d(
     framebuffer: &mut Framebuffer,
     player: &mut Player,
     maze: &Vec<Vec<char>>,
     wall_texture: &Texture,
     floor_texture: &Texture,
-    sky_texture: &Texture,
+    sky: &AnimatedTexture,
+    fog: &Fog,
+    lighting: &LightMap,
+    wall_textures: &WallTextures,
+    world: &World,
+    settings: &Settings,
+    sprites: &[Billboard],
 ) {
-    render_skybox(framebuffer, sky_texture, player);
-    render_floor(framebuffer, floor_texture, player);
+    // Con el farol encendido la niebla y el cielo solo tienen la luz ambiental que los ilumine
+    let dimmed;
+    let (fog, sky_brightness) = if player.lantern.lit {
+        dimmed = fog.dimmed(lighting.ambient);
+        (&dimmed, lighting.ambient)
+    } else {
+        (fog, color::ONE)
+    };
+
+    render_skybox(framebuffer, sky, player, sky_brightness);
+    render_floor(framebuffer, floor_texture, player, maze, fog, lighting, wall_textures);
 
     let num_rays = framebuffer.width;
     let hh = framebuffer.height as f32 / 2.0;
 
     framebuffer.set_current_color(0xFFFFFF);
 
+    // Distancia de la pared pintada en cada píxel, para recortar los sprites: las paredes bajas
+    // solo tapan sus filas y por los huecos de las rejas se ve lo que hay detrás
+    let mut zbuffer = vec![f32::INFINITY; framebuffer.width * framebuffer.height];
+
     for i in 0..num_rays {
         let current_ray = i as f32 / num_rays as f32;
         let mut a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
@@ -73,52 +143,229 @@ pub fn render3d(
             a -= 2.0 * PI;
         }
 
-        let intersect = cast_ray(
+        let hits = cast_ray(
             framebuffer,
             wall_texture,
             maze,
+            world,
             player,
             a,
             false,
         );
 
-        let distance_to_wall = intersect.distance;
+        // Se dibuja de atrás hacia delante para que las paredes bajas tapen a las altas solo en parte
+        for intersect in hits.iter().rev() {
+            let distance_to_wall = intersect.distance;
 
-        if distance_to_wall <= 0.0 || distance_to_wall.is_nan() {
-            println!("Advertencia: distancia inválida para el rayo {}", i);
-            continue;
-        }
+            if distance_to_wall <= 0.0 || distance_to_wall.is_nan() {
+                println!("Advertencia: distancia inválida para el rayo {}", i);
+                continue;
+            }
+
+            let distance_to_projection_plane = 1.0;
+
+            // Altura en pantalla de una pared de una unidad; el ojo está a media altura
+            let stake_height = (hh / distance_to_wall) * distance_to_projection_plane;
+            let stake_bottom_f = hh + (stake_height / 2.0);
+            let stake_top_f = stake_bottom_f - stake_height * intersect.height;
+
+            // Recortar a la pantalla en vez de saltar la columna cuando la pared está muy cerca
+            let stake_top = stake_top_f.max(0.0) as usize;
+            let stake_bottom = (stake_bottom_f as usize).min(framebuffer.height);
+
+            // Las paredes especiales usan su propia textura, quizá animada; las que tienen huecos
+            // dejan ver lo que ya se dibujó detrás
+            let (texture, (scroll_x, scroll_y)) = match wall_textures.get(intersect.impact) {
+                Some(animated) => (animated.frame(), animated.scroll_texels()),
+                None => (wall_texture, (0, 0)),
+            };
+
+            // Mapear la textura correctamente
+            let texture_u = intersect.wall_x() + scroll_x as f32 / texture.width as f32;
+            let scroll_v = scroll_y as f32 / texture.height as f32;
+
+            // Las paredes lejanas usan una versión reducida de la textura para no parpadear
+            let mip = if settings.mipmapping { texture.level(texture.mip_for(stake_height)) } else { texture };
+
+            // Calcomanía de esta cara, orientada para que se lea igual desde cualquier lado
+            let decal = world.decals.at(intersect);
+            let decal_u = if is_mirrored(Face::of(intersect)) { 1.0 - intersect.wall_x() } else { intersect.wall_x() };
+
+            // Sombreado por orientación y niebla se calculan una vez por columna
+            let shade = if intersect.side == 1 { fog.side_shade } else { color::ONE };
+            let fog_factor = fog.factor(distance_to_wall);
+
+            // La pared recibe la luz que llega a la celda libre justo delante de la cara golpeada
+            let front_x = intersect.hit_x - intersect.angle.cos() * 0.01;
+            let front_y = intersect.hit_y - intersect.angle.sin() * 0.01;
+            let mut light = lighting.light_at(front_x.floor() as usize, front_y.floor() as usize, intersect.hit_x, intersect.hit_y);
+            let cone_factor = player.lantern.cone_factor(player.fov * current_ray - player.fov / 2.0);
+            player.lantern.add_light(&mut light, distance_to_wall, cone_factor);
+
+            // Cada espejo atravesado enfría un poco más la imagen reflejada
+            let reflection_tint = (intersect.reflections * MIRROR_TINT_STRENGTH).min(color::ONE);
+
+            for y in stake_top..stake_bottom {
+                // La textura se repite una vez por unidad de altura
+                let texture_v = (y as f32 - stake_top_f) / stake_height + scroll_v;
+                let mut texel = mip.sample(texture_u, texture_v, Wrap::Repeat, settings.bilinear);
+                if is_transparent(texel) {
+                    continue;
+                }
 
-        let distance_to_projection_plane = 1.0;
+                // La calcomanía ocupa la primera unidad de altura desde el suelo
+                let above_floor = (stake_bottom_f - y as f32) / stake_height;
+                if let Some(decal) = decal.filter(|_| above_floor < 1.0) {
+                    let paint = decal.sample(decal_u, 1.0 - above_floor, Wrap::Clamp, false);
+                    let alpha = paint >> 24;
+                    texel = color::lerp(texel, paint, alpha + (alpha >> 7));
+                }
+                let color = color::scale(texel, shade);
+                let color = color::lerp(color, MIRROR_TINT, reflection_tint);
+                let color = color::modulate(color, light);
+                framebuffer.set_current_color(fog.apply(color, fog_factor));
+                framebuffer.point(i, y);
+                zbuffer[y * framebuffer.width + i] = distance_to_wall;
+            }
+        }
+    }
 
-        let stake_height = (hh / distance_to_wall) * distance_to_projection_plane;
+    render_sprites(framebuffer, player, sprites, &zbuffer, fog, lighting);
 
-        let stake_top = (hh - (stake_height / 2.0)) as usize;
-        let stake_bottom = (hh + (stake_height / 2.0)) as usize;
+    // Llamar a la función que renderiza el minimapa
+    render_minimap(framebuffer, player, maze);
+}
 
-        if stake_top >= framebuffer.height || stake_bottom > framebuffer.height {
-            continue;
+// Sprites proyectados en la vista 3D, del más lejano al más cercano, recortados
+// píxel a píxel contra las paredes que tienen delante
+fn render_sprites(framebuffer: &mut Framebuffer, player: &Player, sprites: &[Billboard], zbuffer: &[f32], fog: &Fog, lighting: &LightMap) {
+    let hh = framebuffer.height as f32 / 2.0;
+    let width = framebuffer.width as f32;
+
+    let mut visible: Vec<(f32, f32, &Billboard)> = sprites
+        .iter()
+        .filter_map(|sprite| {
+            let (dx, dy) = (sprite.x - player.pos.x, sprite.y - player.pos.y);
+            let distance = (dx * dx + dy * dy).sqrt();
+            // Ángulo respecto a la mirada, entre -PI y PI
+            let angle = (dy.atan2(dx) - player.a + PI).rem_euclid(2.0 * PI) - PI;
+            (distance > 0.1 && angle.abs() < player.fov).then_some((distance, angle, sprite))
+        })
+        .collect();
+    visible.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    for (distance, angle, sprite) in visible {
+        let texture = sprite.texture;
+
+        // Misma proyección que las paredes: columna según el ángulo y altura según la distancia
+        let center_x = (angle + player.fov / 2.0) / player.fov * width;
+        let unit_height = hh / distance;
+        let bottom = hh + unit_height / 2.0;
+        let height = unit_height * sprite.scale;
+        let top = bottom - height;
+        let sprite_width = height * texture.width as f32 / texture.height as f32;
+        let left = center_x - sprite_width / 2.0;
+
+        let first_column = left.max(0.0) as usize;
+        let last_column = ((left + sprite_width) as usize).min(framebuffer.width);
+        let first_row = top.max(0.0) as usize;
+        let last_row = (bottom as usize).min(framebuffer.height);
+
+        let mut light = lighting.light_at(sprite.x as usize, sprite.y as usize, sprite.x, sprite.y);
+        player.lantern.add_light(&mut light, distance, player.lantern.cone_factor(angle));
+        let fog_factor = fog.factor(distance);
+
+        for x in first_column..last_column {
+            let texture_u = (x as f32 - left) / sprite_width;
+            for y in first_row..last_row {
+                if distance >= zbuffer[y * framebuffer.width + x] {
+                    continue;
+                }
+                let texel = texture.sample(texture_u, (y as f32 - top) / height, Wrap::Clamp, false);
+                if is_transparent(texel) {
+                    continue;
+                }
+                let color = color::modulate(texel, light);
+                framebuffer.set_current_color(fog.apply(color, fog_factor));
+                framebuffer.point(x, y);
+            }
         }
+    }
+}
 
-        // Mapear la textura correctamente
-        let wall_x = if intersect.hit_x % 1.0 > intersect.hit_y % 1.0 {
-            intersect.hit_x % 1.0
-        } else {
-            intersect.hit_y % 1.0
-        };
+// Barra de aceite del farol en la esquina inferior izquierda
+pub fn render_lantern_hud(framebuffer: &mut Framebuffer, lantern: &Lantern) {
+    let bar_width = 150;
+    let bar_height = 12;
+    let x0 = 10;
+    let y0 = framebuffer.height - bar_height - 10;
+    let filled = (lantern.fuel_ratio() * bar_width as f32) as usize;
+
+    for x in x0..(x0 + bar_width) {
+        let color = if x - x0 < filled { 0xFFB030 } else { 0x301808 };
+        framebuffer.set_current_color(color);
+        for y in y0..(y0 + bar_height) {
+            framebuffer.point(x, y);
+        }
+    }
+}
 
-        let texture_x = (wall_x * wall_texture.width as f32) as usize % wall_texture.width;
+// Llaves recogidas, como cuadrados de su color en la esquina inferior derecha
+pub fn render_keys_hud(framebuffer: &mut Framebuffer, keys: &[char], atlas: &Atlas) {
+    let size = 16;
+    let spacing = 6;
+    let y0 = framebuffer.height - size - 10;
+
+    for (i, &key) in keys.iter().enumerate() {
+        let x0 = framebuffer.width - 10 - (i + 1) * (size + spacing);
+        // Icono del atlas si lo hay; si no, un cuadrado del color de la llave
+        match atlas.texture(&format!("key_{}", key_name(key))) {
+            Some(icon) => draw_icon(framebuffer, icon, x0, y0, size, size, None),
+            None => {
+                framebuffer.set_current_color(key_color(key));
+                for x in x0..(x0 + size) {
+                    for y in y0..(y0 + size) {
+                        framebuffer.point(x, y);
+                    }
+                }
+            }
+        }
+    }
+}
 
-        for y in stake_top..stake_bottom {
-            let texture_y = ((y as f32 - hh + stake_height / 2.0) / stake_height * wall_texture.height as f32) as usize % wall_texture.height;
-            let color = wall_texture.data[texture_y * wall_texture.width + texture_x];
-            framebuffer.set_current_color(color);
-            framebuffer.point(i, y);
+// Dibuja una imagen escalada a width x height, saltando los texels transparentes.
+// Con `color` la imagen se usa solo como máscara (las letras de la fuente).
+fn draw_icon(framebuffer: &mut Framebuffer, icon: &Texture, x0: usize, y0: usize, width: usize, height: usize, color: Option<u32>) {
+    for y in 0..height {
+        for x in 0..width {
+            let texel = icon.sample(x as f32 / width as f32, y as f32 / height as f32, Wrap::Clamp, false);
+            if !is_transparent(texel) && x0 + x < framebuffer.width && y0 + y < framebuffer.height {
+                framebuffer.set_current_color(color.unwrap_or(texel));
+                framebuffer.point(x0 + x, y0 + y);
+            }
         }
     }
+}
 
-    // Llamar a la función que renderiza el minimapa
-    render_minimap(framebuffer, player, maze);
+// Texto en `color` con la fuente de mapa de bits del atlas, cada carácter `scale` veces más
+// grande. Devuelve false si el atlas no trae fuente, para usar la TrueType en su lugar.
+#[allow(clippy::too_many_arguments)]
+pub fn render_bitmap_text(framebuffer: &mut Framebuffer, x: usize, y: usize, text: &str, atlas: &Atlas, scale: usize, color: u32) -> bool {
+    if !atlas.has_font() {
+        return false;
+    }
+    let mut pen_x = x;
+    for c in text.chars() {
+        let glyph = atlas.glyph(c).or_else(|| atlas.glyph(c.to_ascii_uppercase()));
+        match glyph {
+            Some(glyph) => {
+                draw_icon(framebuffer, glyph, pen_x, y, glyph.width * scale, glyph.height * scale, Some(color));
+                pen_x += glyph.width * scale;
+            }
+            None => pen_x += atlas.glyph(' ').map_or(8, |space| space.width) * scale,
+        }
+    }
+    true
 }
 
 pub fn render_minimap(framebuffer: &mut Framebuffer, player: &Player, maze: &Vec<Vec<char>>) {
@@ -140,8 +387,22 @@ pub fn render_minimap(framebuffer: &mut Framebuffer, player: &Player, maze: &Vec
             let y0 = minimap_y_offset + row * block_size_y;
 
             match maze[row][col] {
-                '#' => framebuffer.set_current_color(0xFFFFFF), // Color para las paredes
+                '#' | 'T' | '*' => framebuffer.set_current_color(0xFFFFFF), // Color para las paredes
                 'g' => framebuffer.set_current_color(0xFFFF00), // Color para el objetivo (goal)
+                't' | 'u' => framebuffer.set_current_color(0xFF9933), // Antorchas y braseros
+                '~' => framebuffer.set_current_color(0xFF4500), // Lava
+                ';' => framebuffer.set_current_color(0x4A3A1C), // Fango
+                '=' => framebuffer.set_current_color(0x9AC8E8), // Hielo
+                'o' => framebuffer.set_current_color(0xFFC040), // Aceite
+                '$' => framebuffer.set_current_color(0xFFD700), // Tesoro
+                'h' | '_' => framebuffer.set_current_color(0x999999), // Paredes bajas
+                '|' | '+' | 'w' | 'x' => framebuffer.set_current_color(0x6688AA), // Paredes con huecos
+                'D' => framebuffer.set_current_color(0x8B5A2B), // Puertas
+                'O' | '[' | ']' | '^' | 'v' | '/' | '\\' => framebuffer.set_current_color(0xCCCCCC), // Columnas y muros finos
+                'F' => framebuffer.set_current_color(0xAA3344), // Paredes de carne
+                'M' => framebuffer.set_current_color(0xAACCEE), // Espejos
+                '@' | '&' => framebuffer.set_current_color(0x9A30E0), // Teletransportadores y portales
+                c if is_key(c) || key_for_door(c).is_some() => framebuffer.set_current_color(key_color(c)), // Llaves y sus puertas
                 _ => framebuffer.set_current_color(0x000000),   // Color para espacios vacíos
             }
 
@@ -217,7 +478,7 @@ pub fn render_2d(framebuffer: &mut Framebuffer, player: &Player, maze: &Vec<Vec<
 }
 
 fn draw_cell(framebuffer: &mut Framebuffer, xo: usize, yo: usize, block_size_x: usize, block_size_y: usize, cell: char) {
-    if cell == ' ' {
+    if is_open(cell) {
         return;
     }
 
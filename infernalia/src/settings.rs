@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::Path;
+
+// Opciones de calidad gráfica, leídas de un archivo de texto con líneas `clave = valor`.
+// Las claves que falten conservan su valor por defecto.
+pub struct Settings {
+    pub mipmapping: bool, // Texturas reducidas para las paredes lejanas (M para alternar)
+    pub bilinear: bool,   // Mezcla de texels vecinos; apagado se usa el más cercano (B para alternar)
+    pub procedural_textures: bool, // Generar paredes, suelo y cielo aunque haya imágenes
+}
+
+impl Settings {
+    pub fn load<P: AsRef<Path>>(filename: P) -> Settings {
+        let filename = filename.as_ref();
+        let mut settings = Settings { mipmapping: true, bilinear: true, procedural_textures: false };
+
+        let contents = match fs::read_to_string(filename) {
+            Ok(contents) => contents,
+            Err(_) => {
+                println!("No settings file at {}, using defaults.", filename.display());
+                return settings;
+            }
+        };
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                println!("Ignoring settings line: {}", line);
+                continue;
+            };
+            let enabled = matches!(value.trim(), "true" | "on" | "1");
+            match key.trim() {
+                "mipmapping" => settings.mipmapping = enabled,
+                "bilinear" => settings.bilinear = enabled,
+                "procedural_textures" => settings.procedural_textures = enabled,
+                other => println!("Unknown setting: {}", other),
+            }
+        }
+
+        settings
+    }
+}
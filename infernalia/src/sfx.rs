@@ -1,14 +1,42 @@
 use rodio::{Decoder, OutputStream, Sink, Source};
-use std::fs::File;
-use std::io::BufReader;
+use std::io::Cursor;
 use std::sync::Arc;
 
+// Archivo de sonido ya leído (lo carga `Assets` una sola vez). Sin datos es silencio.
+#[derive(Clone)]
+pub struct Sound {
+    data: Arc<[u8]>,
+}
+
+impl Sound {
+    pub fn new(data: Vec<u8>) -> Self {
+        Sound { data: data.into() }
+    }
+
+    pub fn silence() -> Self {
+        Sound::new(Vec::new())
+    }
+
+    // Un decodificador nuevo que reproduce el sonido desde el principio
+    fn decoder(&self) -> Option<Decoder<Cursor<Arc<[u8]>>>> {
+        if self.data.is_empty() {
+            return None;
+        }
+        match Decoder::new(Cursor::new(Arc::clone(&self.data))) {
+            Ok(decoder) => Some(decoder),
+            Err(e) => {
+                println!("Failed to decode sound: {}", e);
+                None
+            }
+        }
+    }
+}
+
 pub struct SoundManager {
     _stream: OutputStream,
     stream_handle: Arc<rodio::OutputStreamHandle>,
     footstep_sink: Sink,
     ambient_sink: Sink, // Sink to handle the ambient sound
-    music_sink: Sink,   // Sink to handle the music
 }
 
 impl SoundManager {
@@ -16,21 +44,18 @@ impl SoundManager {
         let (_stream, stream_handle) = OutputStream::try_default().unwrap();
         let footstep_sink = Sink::try_new(&stream_handle).unwrap();
         let ambient_sink = Sink::try_new(&stream_handle).unwrap();
-        let music_sink = Sink::try_new(&stream_handle).unwrap(); // Initialize music sink
 
         SoundManager {
             _stream,
             stream_handle: Arc::new(stream_handle),
             footstep_sink,
             ambient_sink,
-            music_sink,
         }
     }
 
-    pub fn play_footsteps(&self, file_path: &str) {
+    pub fn play_footsteps(&self, sound: &Sound) {
         if self.footstep_sink.empty() {
-            let file = File::open(file_path).expect("Failed to open footstep sound file");
-            let source = Decoder::new(BufReader::new(file)).expect("Failed to decode footstep sound file");
+            let Some(source) = sound.decoder() else { return };
             self.footstep_sink.append(source.repeat_infinite()); // Repeat the sound infinitely
             self.footstep_sink.play();
         }
@@ -40,10 +65,9 @@ impl SoundManager {
         self.footstep_sink.stop();
     }
 
-    pub fn play_ambient(&self, file_path: &str) {
+    pub fn play_ambient(&self, sound: &Sound) {
         if self.ambient_sink.empty() {
-            let file = File::open(file_path).expect("Failed to open ambient sound file");
-            let source = Decoder::new(BufReader::new(file)).expect("Failed to decode ambient sound file");
+            let Some(source) = sound.decoder() else { return };
             self.ambient_sink.append(source.repeat_infinite()); // Repeat the sound infinitely
             self.ambient_sink.set_volume(0.3); // Adjust the volume for ambient sound
             self.ambient_sink.play();
@@ -54,11 +78,14 @@ impl SoundManager {
         self.ambient_sink.stop();
     }
 
-    pub fn play_music(&self, file_path: &str) {
-        // Reproducir la música desde el inicio
-        let file = File::open(file_path).expect("Failed to open music file");
-        let source = Decoder::new(BufReader::new(file)).expect("Failed to decode music file");
-        self.music_sink.append(source); // No repeat, play once
-        self.music_sink.play();
+    // Congela pasos y ambiente (menú de pausa); `resume` los sigue donde se quedaron
+    pub fn pause(&self) {
+        self.footstep_sink.pause();
+        self.ambient_sink.pause();
+    }
+
+    pub fn resume(&self) {
+        self.footstep_sink.play();
+        self.ambient_sink.play();
     }
 }
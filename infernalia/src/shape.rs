@@ -0,0 +1,81 @@
+use std::f32::consts::TAU;
+
+// Paredes que ocupan solo parte de su celda: columnas redondas ('O'), muros finos
+// pegados a un borde ('[' oeste, ']' este, '^' norte, 'v' sur) y diagonales
+// ('/' de la esquina inferior izquierda a la superior derecha, '\' al revés).
+// Los rayos y el jugador chocan contra la figura exacta, no contra la celda entera.
+
+const PILLAR_RADIUS: f32 = 0.3;
+
+pub enum Shape {
+    Circle { x: f32, y: f32, radius: f32 },
+    Segment { x0: f32, y0: f32, x1: f32, y1: f32 },
+}
+
+// Figura de la celda (x, y), si es una pared parcial
+pub fn cell_shape(cell: char, x: usize, y: usize) -> Option<Shape> {
+    let (x, y) = (x as f32, y as f32);
+    let segment = |x0: f32, y0: f32, x1: f32, y1: f32| Some(Shape::Segment { x0: x + x0, y0: y + y0, x1: x + x1, y1: y + y1 });
+    match cell {
+        'O' => Some(Shape::Circle { x: x + 0.5, y: y + 0.5, radius: PILLAR_RADIUS }),
+        '[' => segment(0.0, 0.0, 0.0, 1.0),
+        ']' => segment(1.0, 0.0, 1.0, 1.0),
+        '^' => segment(0.0, 0.0, 1.0, 0.0),
+        'v' => segment(0.0, 1.0, 1.0, 1.0),
+        '/' => segment(0.0, 1.0, 1.0, 0.0),
+        '\\' => segment(0.0, 0.0, 1.0, 1.0),
+        _ => None,
+    }
+}
+
+impl Shape {
+    // Primer corte del rayo con la figura: distancia, posición a lo largo de la
+    // superficie (0.0..1.0) para la textura y cara para el sombreado (como en `Intersect`)
+    pub fn intersect(&self, origin_x: f32, origin_y: f32, cos_angle: f32, sin_angle: f32) -> Option<(f32, f32, usize)> {
+        match *self {
+            Shape::Circle { x, y, radius } => {
+                let (fx, fy) = (origin_x - x, origin_y - y);
+                let b = fx * cos_angle + fy * sin_angle;
+                let c = fx * fx + fy * fy - radius * radius;
+                let discriminant = b * b - c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let distance = -b - discriminant.sqrt();
+                if distance <= 0.0 {
+                    return None;
+                }
+                // La textura da dos vueltas alrededor de la columna para no estirarse
+                let (hit_x, hit_y) = (origin_x + cos_angle * distance, origin_y + sin_angle * distance);
+                let around = ((hit_y - y).atan2(hit_x - x) / TAU).rem_euclid(1.0);
+                Some((distance, (around * 2.0).fract(), 0))
+            }
+            Shape::Segment { x0, y0, x1, y1 } => {
+                let (ex, ey) = (x1 - x0, y1 - y0);
+                let denominator = cos_angle * ey - sin_angle * ex;
+                if denominator.abs() < 1e-6 {
+                    return None; // Rayo paralelo al muro
+                }
+                let (px, py) = (x0 - origin_x, y0 - origin_y);
+                let distance = (px * ey - py * ex) / denominator;
+                let along = (px * sin_angle - py * cos_angle) / denominator;
+                if distance <= 0.0 || !(0.0..=1.0).contains(&along) {
+                    return None;
+                }
+                Some((distance, along, if ex.abs() > ey.abs() { 1 } else { 0 }))
+            }
+        }
+    }
+
+    // Distancia desde un punto hasta el borde de la figura (negativa dentro de una columna)
+    pub fn distance_to(&self, px: f32, py: f32) -> f32 {
+        match *self {
+            Shape::Circle { x, y, radius } => ((px - x).powi(2) + (py - y).powi(2)).sqrt() - radius,
+            Shape::Segment { x0, y0, x1, y1 } => {
+                let (ex, ey) = (x1 - x0, y1 - y0);
+                let t = (((px - x0) * ex + (py - y0) * ey) / (ex * ex + ey * ey)).clamp(0.0, 1.0);
+                ((px - x0 - ex * t).powi(2) + (py - y0 - ey * t).powi(2)).sqrt()
+            }
+        }
+    }
+}
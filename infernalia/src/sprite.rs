@@ -1,3 +1,5 @@
+use crate::texture::Texture;
+
 pub struct AnimatedSprite {
     frames: Vec<Texture>,
     current_frame: usize,
@@ -23,15 +25,41 @@ impl AnimatedSprite {
         }
     }
 
-    pub fn render(&self, framebuffer: &mut Framebuffer, x: usize, y: usize) {
-        let texture = &self.frames[self.current_frame];
-        // Render the current frame of the sprite
-        for j in 0..texture.height {
-            for i in 0..texture.width {
-                let color = texture.get_pixel(i, j);
-                framebuffer.set_current_color(color);
-                framebuffer.point(x + i, y + j);
-            }
-        }
+    pub fn frame(&self) -> &Texture {
+        &self.frames[self.current_frame]
+    }
+}
+
+// Sprite en el mundo: una imagen siempre de cara a la cámara, apoyada en el suelo
+// en (x, y) y de `scale` unidades de alto. `kind` es el carácter del mapa que
+// representa, para quitarlo cuando el objeto desaparece de su celda.
+pub struct Sprite {
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32,
+    pub animation: AnimatedSprite,
+    pub kind: char,
+}
+
+impl Sprite {
+    pub fn new(x: f32, y: f32, scale: f32, animation: AnimatedSprite, kind: char) -> Self {
+        Sprite { x, y, scale, animation, kind }
     }
+
+    // Si su celda todavía tiene el objeto que representa (las llaves, por ejemplo, se recogen)
+    pub fn still_at(&self, maze: &[Vec<char>]) -> bool {
+        maze.get(self.y as usize).and_then(|row| row.get(self.x as usize)) == Some(&self.kind)
+    }
+
+    pub fn billboard(&self) -> Billboard<'_> {
+        Billboard { x: self.x, y: self.y, scale: self.scale, texture: self.animation.frame() }
+    }
+}
+
+// Lo que se dibuja en un fotograma: la imagen que toca de un objeto o un monstruo
+pub struct Billboard<'a> {
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32,
+    pub texture: &'a Texture,
 }
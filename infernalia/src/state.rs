@@ -0,0 +1,540 @@
+use std::time::Instant;
+
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window};
+
+use crate::framebuffer::Framebuffer;
+use crate::game::{Game, ViewMode};
+use crate::render::{render_2d, render3d, render_bitmap_text, render_keys_hud, render_lantern_hud};
+
+// Las pantallas del juego. Cada estado atiende su entrada, actualiza la partida y dibuja
+// su fotograma, y pasa a otro solo devolviendo una `Transition`; al entrar en un estado
+// se ajustan los sonidos. Una pantalla nueva es un estado más aquí, sin tocar el resto.
+
+#[derive(Clone)]
+pub enum GameState {
+    Title,
+    Playing,
+    Paused { selected: usize },        // Opción marcada del menú de pausa
+    LevelComplete,                     // Entre un círculo y el siguiente
+    GameOver,                          // Tras el último círculo
+    Settings { back: Box<GameState> }, // Al salir se vuelve a `back`
+}
+
+pub enum Transition {
+    Stay,
+    To(GameState),
+    Quit,
+}
+
+impl GameState {
+    pub fn enter(&self, game: &mut Game, window: &Window) {
+        match self {
+            GameState::Playing => {
+                // Que el ratón movido mientras no se jugaba no haga girar al jugador de golpe
+                if let Some(mouse_pos) = window.get_mouse_pos(MouseMode::Pass) {
+                    game.last_mouse = mouse_pos;
+                }
+                // Ni que el tiempo en pausa cuente como un paso muy largo
+                game.player.last_update = Instant::now();
+                game.sound_manager.resume();
+            }
+            GameState::Paused { .. } => {
+                game.sound_manager.stop_footsteps();
+                game.sound_manager.pause();
+                game.mouse_was_down = window.get_mouse_down(MouseButton::Left);
+            }
+            GameState::LevelComplete => game.sound_manager.stop_footsteps(),
+            GameState::GameOver => {
+                // Detener todos los sonidos en curso
+                game.sound_manager.stop_footsteps();
+                game.sound_manager.stop_ambient();
+            }
+            GameState::Title | GameState::Settings { .. } => {}
+        }
+    }
+
+    pub fn input(&mut self, game: &mut Game, window: &Window) -> Transition {
+        match self {
+            GameState::Title => title_input(window),
+            GameState::Playing => playing_input(game, window),
+            GameState::Paused { selected } => paused_input(game, window, selected),
+            GameState::LevelComplete => level_complete_input(game, window),
+            GameState::GameOver => game_over_input(window),
+            GameState::Settings { back } => settings_input(game, window, back),
+        }
+    }
+
+    pub fn update(&self, game: &mut Game, delta_time: f32) -> Transition {
+        match self {
+            GameState::Playing => playing_update(game, delta_time),
+            _ => Transition::Stay, // El resto de pantallas dejan la partida congelada
+        }
+    }
+
+    pub fn render(&self, game: &mut Game, framebuffer: &mut Framebuffer) {
+        match self {
+            GameState::Title => title_render(game, framebuffer),
+            GameState::Playing => playing_render(game, framebuffer),
+            GameState::Paused { selected } => paused_render(game, framebuffer, *selected),
+            GameState::LevelComplete => level_complete_render(game, framebuffer),
+            GameState::GameOver => game_over_render(game, framebuffer),
+            GameState::Settings { back } => settings_render(game, framebuffer, back),
+        }
+    }
+}
+
+// Tiempo en minutos y segundos, como "3:07"
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn draw_overlay(framebuffer: &mut Framebuffer, opacity: u8) {
+    let overlay_color = (opacity as u32) << 24; // Semitransparente negro
+
+    for y in 0..framebuffer.height {
+        for x in 0..framebuffer.width {
+            framebuffer.buffer[y * framebuffer.width + x] = overlay_color;
+        }
+    }
+}
+
+// Texto del HUD con la fuente de mapa de bits del atlas, o con la TrueType si no hay
+fn draw_hud_text(game: &Game, framebuffer: &mut Framebuffer, x: usize, y: usize, text: &str, color: u32) {
+    if !render_bitmap_text(framebuffer, x, y, text, &game.atlas, 2, color) {
+        framebuffer.set_current_color(color);
+        game.draw_text(framebuffer, x, y, text, 24.0);
+    }
+}
+
+// --- Title ---
+
+fn title_input(window: &Window) -> Transition {
+    if window.is_key_pressed(Key::Escape, KeyRepeat::No) {
+        return Transition::Quit;
+    }
+    if window.is_key_pressed(Key::S, KeyRepeat::No) {
+        return Transition::To(GameState::Settings { back: Box::new(GameState::Title) });
+    }
+    // Wait for input to start the game
+    if window.get_mouse_down(MouseButton::Left) || window.is_key_down(Key::Enter) {
+        return Transition::To(GameState::Playing);
+    }
+    Transition::Stay
+}
+
+fn title_render(game: &Game, framebuffer: &mut Framebuffer) {
+    // Dibujar el overlay semitransparente
+    draw_overlay(framebuffer, 50); // 128 es el valor de opacidad (0-255)
+
+    // Render initial overlay text
+    let title_x = (framebuffer.width - 300) / 2;
+    let title_y = framebuffer.height / 2 - 100;
+
+    let press_anywhere_x = (framebuffer.width - 200) / 2;
+    let press_anywhere_y = framebuffer.height / 2;
+
+    framebuffer.set_current_color(0xFFFF00); // White color for text
+    game.draw_text(framebuffer, title_x, title_y, "INFERNALIA", 48.0);
+    game.draw_text(framebuffer, press_anywhere_x, press_anywhere_y, "Press Anywhere to Start", 24.0);
+    game.draw_text(framebuffer, press_anywhere_x + 30, press_anywhere_y + 40, "S for settings", 20.0);
+}
+
+// --- Playing ---
+
+fn playing_input(game: &mut Game, window: &Window) -> Transition {
+    if window.is_key_pressed(Key::Escape, KeyRepeat::No) || window.is_key_pressed(Key::P, KeyRepeat::No) {
+        return Transition::To(GameState::Paused { selected: 0 });
+    }
+
+    let (player, level) = (&mut game.player, &mut game.level);
+    let mut moving = false;
+
+    if window.is_key_down(Key::W) {
+        player.move_forward(1.0, &level.maze, &level.world);
+        moving = true;
+    }
+    if window.is_key_down(Key::S) {
+        player.move_forward(-1.0, &level.maze, &level.world);
+        moving = true;
+    }
+    if window.is_key_down(Key::A) {
+        player.strafe(1.0, &level.maze, &level.world);
+        moving = true;
+    }
+    if window.is_key_down(Key::D) {
+        player.strafe(-1.0, &level.maze, &level.world);
+        moving = true;
+    }
+
+    if moving {
+        game.sound_manager.play_footsteps(&game.footsteps);
+    } else {
+        game.sound_manager.stop_footsteps();
+    }
+
+    if window.is_key_down(Key::Key1) {
+        game.view_mode = ViewMode::View2D;
+    } else if window.is_key_down(Key::Key2) {
+        game.view_mode = ViewMode::View3D;
+    }
+    if window.is_key_pressed(Key::F, KeyRepeat::No) {
+        game.level.fog.cycle_mode();
+    }
+    if window.is_key_pressed(Key::M, KeyRepeat::No) {
+        toggle_mipmapping(game);
+    }
+    if window.is_key_pressed(Key::B, KeyRepeat::No) {
+        toggle_bilinear(game);
+    }
+    if window.is_key_pressed(Key::L, KeyRepeat::No) {
+        game.toggle_lantern();
+    }
+
+    // E para abrir la puerta o empujar la pared secreta que el jugador tiene delante
+    if window.is_key_pressed(Key::E, KeyRepeat::No) {
+        game.level.world.use_in_front(&game.player, &mut game.level.maze);
+    }
+
+    // Captura el movimiento del mouse solo si está dentro de los límites de la ventana
+    if let Some(mouse_pos) = window.get_mouse_pos(MouseMode::Pass) {
+        let mouse_dx = mouse_pos.0 - game.last_mouse.0;
+        let (window_width, window_height) = window.get_size();
+
+        if mouse_pos.0 >= 0.0 && mouse_pos.0 <= window_width as f32 &&
+            mouse_pos.1 >= 0.0 && mouse_pos.1 <= window_height as f32 {
+            if mouse_dx.abs() > 0.0 {
+                let sensitivity_multiplier = 2.0;
+                game.player.rotate(mouse_dx * sensitivity_multiplier);
+            }
+        }
+
+        game.last_mouse = mouse_pos;
+    }
+
+    Transition::Stay
+}
+
+fn playing_update(game: &mut Game, delta_time: f32) -> Transition {
+    game.level_time += delta_time;
+    game.clock += delta_time;
+    if delta_time > 0.0 {
+        game.fps = (1.0 / delta_time) as usize;
+    }
+
+    let (player, level) = (&mut game.player, &mut game.level);
+
+    if level.world.teleporters.update(player) {
+        println!("Teleported to x = {:.1}, y = {:.1}.", player.pos.x, player.pos.y);
+    }
+
+    if game.reached_goal() {
+        game.total_time += game.level_time;
+        // Tras el noveno círculo se acaba la partida
+        return Transition::To(if game.is_last_circle() { GameState::GameOver } else { GameState::LevelComplete });
+    }
+
+    let (player, level) = (&mut game.player, &mut game.level);
+
+    // Verificación de si el jugador está fuera de los límites
+    if player.is_out_of_bounds(&level.maze) {
+        println!("Advertencia: Jugador fuera de los límites (x: {}, y: {}). Respawneando...", player.pos.x, player.pos.y);
+        player.respawn(&level.maze);
+    }
+
+    if level.world.update(delta_time, player, &mut level.maze) {
+        level.lighting.rebuild_visibility(&level.maze);
+    }
+
+    player.lantern.update(delta_time);
+    if player.collect_fuel(&mut level.maze) {
+        println!("Oil collected: {:.0}s of light left.", player.lantern.fuel);
+    }
+    if let Some(key) = player.collect_key(&mut level.maze) {
+        println!("Key '{}' collected.", key);
+    }
+    if player.collect_treasure(&mut level.maze) {
+        println!("Treasure found! ({} so far)", player.treasures);
+    }
+
+    level.lighting.update(game.clock);
+    level.wall_textures.update(delta_time);
+    // Los objetos recogidos dejan de verse
+    level.sprites.retain(|sprite| sprite.still_at(&level.maze));
+    for sprite in level.sprites.iter_mut() {
+        sprite.animation.update(delta_time);
+    }
+    for monster in level.monsters.iter_mut() {
+        monster.update(delta_time, &level.maze, &level.world, player);
+    }
+    level.monsters.retain(|monster| !monster.is_gone());
+    level.sky.update(delta_time);
+
+    if game.banner_time > 0.0 {
+        game.banner_time -= delta_time;
+    }
+
+    Transition::Stay
+}
+
+fn playing_render(game: &mut Game, framebuffer: &mut Framebuffer) {
+    let (player, level) = (&mut game.player, &game.level);
+
+    // Render the maze and player view
+    match game.view_mode {
+        ViewMode::View2D => {
+            render_2d(framebuffer, player, &level.maze);
+        }
+        ViewMode::View3D => {
+            let billboards: Vec<_> = level
+                .sprites
+                .iter()
+                .map(|sprite| sprite.billboard())
+                .chain(level.monsters.iter().map(|monster| monster.billboard(player.pos.x, player.pos.y)))
+                .collect();
+            render3d(framebuffer, player, &level.maze, &level.wall_texture, &level.floor_texture, &level.sky, &level.fog, &level.lighting, &level.wall_textures, &level.world, &game.settings, &billboards);
+        }
+    }
+
+    if player.lantern.lit {
+        render_lantern_hud(framebuffer, &player.lantern);
+    }
+    render_keys_hud(framebuffer, &player.keys, &game.atlas);
+
+    // Nombre del círculo al entrar en él
+    if game.banner_time > 0.0 {
+        let circle = &game.campaign.circles[level.number];
+        let x = framebuffer.width / 2 - 120; // A la derecha del minimapa
+        framebuffer.set_current_color(0xFFD27A);
+        game.draw_text(framebuffer, x, 40, &circle.name, 40.0);
+        game.draw_text(framebuffer, x, 84, &circle.subtitle, 22.0);
+    }
+    if game.player.treasures > 0 {
+        let text = format!("Tesoros: {}", game.player.treasures);
+        draw_hud_text(game, framebuffer, framebuffer.width - 140, 60, &text, 0xFFD700);
+    }
+    // Círculo actual y tiempo que lleva en él
+    let text = format!("{}/{} {}", game.level.number + 1, game.campaign.circles.len(), format_time(game.level_time));
+    draw_hud_text(game, framebuffer, framebuffer.width - 140, 90, &text, 0xFFD27A);
+
+    let text = format!("FPS: {}", game.fps);
+    draw_hud_text(game, framebuffer, framebuffer.width - 100, 30, &text, 0xFFFFFF);
+}
+
+// --- Paused ---
+
+#[derive(Clone, Copy)]
+enum PauseEntry {
+    Resume,
+    RestartLevel,
+    NewMaze,
+    Settings,
+    Quit,
+}
+
+const PAUSE_MENU: [(PauseEntry, &str); 5] = [
+    (PauseEntry::Resume, "Resume"),
+    (PauseEntry::RestartLevel, "Restart Level"),
+    (PauseEntry::NewMaze, "New Maze"),
+    (PauseEntry::Settings, "Settings"),
+    (PauseEntry::Quit, "Quit"),
+];
+
+// Recuadro (x, y, ancho, alto) de cada opción del menú, centrado en una pantalla de ese tamaño
+fn pause_entry_rect(index: usize, width: usize, height: usize) -> (usize, usize, usize, usize) {
+    let (entry_width, entry_height) = (260, 44);
+    ((width - entry_width) / 2, height / 2 - 80 + index * entry_height, entry_width, entry_height)
+}
+
+fn paused_input(game: &mut Game, window: &Window, selected: &mut usize) -> Transition {
+    if window.is_key_pressed(Key::Escape, KeyRepeat::No) || window.is_key_pressed(Key::P, KeyRepeat::No) {
+        return Transition::To(GameState::Playing);
+    }
+
+    let count = PAUSE_MENU.len();
+    if window.is_key_pressed(Key::Up, KeyRepeat::Yes) || window.is_key_pressed(Key::W, KeyRepeat::Yes) {
+        *selected = (*selected + count - 1) % count;
+    }
+    if window.is_key_pressed(Key::Down, KeyRepeat::Yes) || window.is_key_pressed(Key::S, KeyRepeat::Yes) {
+        *selected = (*selected + 1) % count;
+    }
+    let mut chosen = window.is_key_pressed(Key::Enter, KeyRepeat::No) || window.is_key_pressed(Key::Space, KeyRepeat::No);
+
+    // Con el ratón: al moverlo se marca la opción que tiene debajo y un clic la elige
+    let mouse_down = window.get_mouse_down(MouseButton::Left);
+    let clicked = mouse_down && !game.mouse_was_down;
+    game.mouse_was_down = mouse_down;
+    if let Some((mouse_x, mouse_y)) = window.get_mouse_pos(MouseMode::Discard) {
+        let (width, height) = window.get_size();
+        let hovered = (0..count).find(|&i| {
+            let (x, y, w, h) = pause_entry_rect(i, width, height);
+            mouse_x >= x as f32 && mouse_x < (x + w) as f32 && mouse_y >= y as f32 && mouse_y < (y + h) as f32
+        });
+        if let Some(index) = hovered {
+            if clicked || (mouse_x, mouse_y) != game.last_mouse {
+                *selected = index;
+            }
+            chosen |= clicked;
+        }
+        game.last_mouse = (mouse_x, mouse_y);
+    }
+
+    if !chosen {
+        return Transition::Stay;
+    }
+    match PAUSE_MENU[*selected].0 {
+        PauseEntry::Resume => Transition::To(GameState::Playing),
+        PauseEntry::RestartLevel => restart(game, false),
+        PauseEntry::NewMaze => restart(game, true),
+        PauseEntry::Settings => Transition::To(GameState::Settings { back: Box::new(GameState::Paused { selected: *selected }) }),
+        PauseEntry::Quit => Transition::Quit,
+    }
+}
+
+fn restart(game: &mut Game, new_maze: bool) -> Transition {
+    match game.restart_circle(new_maze) {
+        Ok(()) => Transition::To(GameState::Playing),
+        Err(e) => {
+            println!("Error: {}", e);
+            Transition::Stay
+        }
+    }
+}
+
+fn fill_rect(framebuffer: &mut Framebuffer, x: usize, y: usize, width: usize, height: usize, color: u32) {
+    for row in y..(y + height).min(framebuffer.height) {
+        for column in x..(x + width).min(framebuffer.width) {
+            framebuffer.buffer[row * framebuffer.width + column] = color;
+        }
+    }
+}
+
+fn paused_render(game: &mut Game, framebuffer: &mut Framebuffer, selected: usize) {
+    // La partida congelada, oscurecida, debajo del menú
+    playing_render(game, framebuffer);
+    framebuffer.dim(0.4);
+
+    let (menu_x, menu_y, _, _) = pause_entry_rect(0, framebuffer.width, framebuffer.height);
+    framebuffer.set_current_color(0xFFFF00);
+    game.draw_text(framebuffer, menu_x + 40, menu_y - 80, "PAUSED", 48.0);
+
+    for (i, (_, label)) in PAUSE_MENU.iter().enumerate() {
+        let (x, y, width, height) = pause_entry_rect(i, framebuffer.width, framebuffer.height);
+        if i == selected {
+            fill_rect(framebuffer, x, y, width, height - 6, 0x5A1010);
+            framebuffer.set_current_color(0xFFFF00);
+        } else {
+            framebuffer.set_current_color(0xC8C8C8);
+        }
+        game.draw_text(framebuffer, x + 20, y + 4, label, 28.0);
+    }
+}
+
+// --- LevelComplete ---
+
+fn level_complete_input(game: &mut Game, window: &Window) -> Transition {
+    if window.is_key_pressed(Key::Escape, KeyRepeat::No) {
+        return Transition::Quit;
+    }
+    // Se baja al siguiente círculo, más grande y difícil que este
+    if window.get_mouse_down(MouseButton::Left) || window.is_key_pressed(Key::Enter, KeyRepeat::No) {
+        match game.enter_circle(game.level.number + 1) {
+            Ok(()) => return Transition::To(GameState::Playing),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+    Transition::Stay
+}
+
+fn level_complete_render(game: &Game, framebuffer: &mut Framebuffer) {
+    draw_overlay(framebuffer, 50);
+
+    let cleared = &game.campaign.circles[game.level.number];
+    let next = &game.campaign.circles[game.level.number + 1];
+    let text_x = (framebuffer.width - 500) / 2;
+    let text_y = framebuffer.height / 2 - 120;
+
+    framebuffer.set_current_color(0xFFFF00);
+    game.draw_text(framebuffer, text_x, text_y, &format!("{} FELLED!", cleared.name.to_uppercase()), 48.0);
+    game.draw_text(framebuffer, text_x, text_y + 70, &format!("Time: {}   Total: {}", format_time(game.level_time), format_time(game.total_time)), 24.0);
+    game.draw_text(framebuffer, text_x, text_y + 110, &format!("Next: circle {}, {}", game.level.number + 2, next.name), 24.0);
+    game.draw_text(framebuffer, text_x, text_y + 150, "Press Enter to descend", 24.0);
+}
+
+// --- GameOver ---
+
+fn game_over_input(window: &Window) -> Transition {
+    if window.is_key_pressed(Key::Escape, KeyRepeat::No) {
+        return Transition::Quit;
+    }
+    Transition::Stay
+}
+
+fn game_over_render(game: &Game, framebuffer: &mut Framebuffer) {
+    // Dibujar el overlay semitransparente
+    draw_overlay(framebuffer, 50);
+
+    // Render final overlay text
+    let title_x = (framebuffer.width - 500) / 2;
+    let title_y = framebuffer.height / 2 - 100;
+
+    framebuffer.set_current_color(0xFFFF00); // Yellow color for text
+    game.draw_text(framebuffer, title_x, title_y, "LABRYNTH FELLED!", 48.0);
+    game.draw_text(framebuffer, title_x, title_y + 70, &format!("Total time: {}", format_time(game.total_time)), 24.0);
+}
+
+// --- Settings ---
+
+fn toggle_mipmapping(game: &mut Game) {
+    game.settings.mipmapping = !game.settings.mipmapping;
+    println!("Mipmapping: {}", if game.settings.mipmapping { "on" } else { "off" });
+}
+
+fn toggle_bilinear(game: &mut Game) {
+    game.settings.bilinear = !game.settings.bilinear;
+    println!("Bilinear filtering: {}", if game.settings.bilinear { "on" } else { "off" });
+}
+
+fn settings_input(game: &mut Game, window: &Window, back: &GameState) -> Transition {
+    if window.is_key_pressed(Key::Escape, KeyRepeat::No) || window.is_key_pressed(Key::Backspace, KeyRepeat::No) {
+        return Transition::To(back.clone());
+    }
+    if window.is_key_pressed(Key::M, KeyRepeat::No) {
+        toggle_mipmapping(game);
+    }
+    if window.is_key_pressed(Key::B, KeyRepeat::No) {
+        toggle_bilinear(game);
+    }
+    // Se nota al generar el siguiente círculo
+    if window.is_key_pressed(Key::T, KeyRepeat::No) {
+        game.settings.procedural_textures = !game.settings.procedural_textures;
+        println!("Procedural textures: {}", if game.settings.procedural_textures { "on" } else { "off" });
+    }
+    Transition::Stay
+}
+
+fn settings_render(game: &mut Game, framebuffer: &mut Framebuffer, back: &GameState) {
+    // Desde el menú de pausa, sobre la partida congelada
+    if matches!(back, GameState::Paused { .. }) {
+        playing_render(game, framebuffer);
+        framebuffer.dim(0.25);
+    } else {
+        draw_overlay(framebuffer, 50);
+    }
+
+    let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+    let lines = [
+        format!("M   Mipmapping: {}", on_off(game.settings.mipmapping)),
+        format!("B   Bilinear filtering: {}", on_off(game.settings.bilinear)),
+        format!("T   Procedural textures: {} (next circle)", on_off(game.settings.procedural_textures)),
+        "Esc   Back".to_string(),
+    ];
+    let text_x = (framebuffer.width - 400) / 2;
+    let text_y = framebuffer.height / 2 - 120;
+
+    framebuffer.set_current_color(0xFFFF00);
+    game.draw_text(framebuffer, text_x, text_y, "SETTINGS", 48.0);
+    for (i, line) in lines.iter().enumerate() {
+        game.draw_text(framebuffer, text_x, text_y + 80 + i * 36, line, 24.0);
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::map::{is_open, is_portal, is_teleport_pad};
+use crate::player::Player;
+
+// Teletransportadores ('@'): al pisar uno el jugador aparece en su destino, mirando
+// hacia donde diga el nivel o conservando su orientación.
+// Portales ('&'): paredes que no son paredes. Se ve y se camina a través de la cara
+// libre de un portal como si fuera la del portal enlazado, girando lo necesario.
+
+pub struct Teleport {
+    pub target: (usize, usize),
+    pub facing: Option<f32>, // Ángulo de llegada en radianes; None conserva el del jugador
+}
+
+pub struct Portal {
+    pub target: (usize, usize),
+    pub normal: (isize, isize), // Dirección de la cara libre del portal
+}
+
+impl Portal {
+    fn face(x: usize, y: usize, normal: (isize, isize)) -> (f32, f32) {
+        (x as f32 + 0.5 + normal.0 as f32 * 0.5, y as f32 + 0.5 + normal.1 as f32 * 0.5)
+    }
+}
+
+pub struct Teleporters {
+    pads: HashMap<(usize, usize), Teleport>,
+    portals: HashMap<(usize, usize), Portal>,
+    last_cell: (usize, usize), // Así no se rebota al llegar sobre otro teletransportador
+}
+
+impl Teleporters {
+    // Enlaces del nivel (`:teleport x y destino_x destino_y [grados]` y `:portal x y destino_x destino_y`).
+    // Los teletransportadores y portales sin enlace se emparejan entre sí en orden de lectura.
+    pub fn from_level(maze: &[Vec<char>], directives: &[Vec<String>]) -> Self {
+        let mut pads = HashMap::new();
+        let mut portal_links = HashMap::new();
+
+        for words in directives {
+            let numbers: Vec<f32> = words.iter().skip(1).filter_map(|word| word.parse().ok()).collect();
+            if numbers.len() < 4 || numbers[..4].iter().any(|&n| n < 0.0) {
+                continue;
+            }
+            let from = (numbers[0] as usize, numbers[1] as usize);
+            let target = (numbers[2] as usize, numbers[3] as usize);
+            match words[0].as_str() {
+                "teleport" => {
+                    let facing = numbers.get(4).map(|degrees| degrees.to_radians());
+                    pads.insert(from, Teleport { target, facing });
+                }
+                "portal" => {
+                    portal_links.insert(from, target);
+                    portal_links.entry(target).or_insert(from);
+                }
+                _ => {}
+            }
+        }
+
+        let mut loose_pads = Vec::new();
+        let mut loose_portals = Vec::new();
+        for (y, row) in maze.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if is_teleport_pad(cell) && !pads.contains_key(&(x, y)) {
+                    loose_pads.push((x, y));
+                }
+                if is_portal(cell) && !portal_links.contains_key(&(x, y)) {
+                    loose_portals.push((x, y));
+                }
+            }
+        }
+        for pair in loose_pads.chunks_exact(2) {
+            pads.insert(pair[0], Teleport { target: pair[1], facing: None });
+            pads.insert(pair[1], Teleport { target: pair[0], facing: None });
+        }
+        for pair in loose_portals.chunks_exact(2) {
+            portal_links.insert(pair[0], pair[1]);
+            portal_links.insert(pair[1], pair[0]);
+        }
+
+        // La cara libre de cada portal es la de su primera vecina transitable
+        let open = |x: isize, y: isize| {
+            y >= 0 && x >= 0 && maze.get(y as usize).and_then(|row| row.get(x as usize)).is_some_and(|&c| is_open(c))
+        };
+        let mut portals = HashMap::new();
+        for (&(x, y), &target) in &portal_links {
+            let normal = [(0, 1), (1, 0), (0, -1), (-1, 0)]
+                .into_iter()
+                .find(|&(dx, dy)| open(x as isize + dx, y as isize + dy));
+            if let Some(normal) = normal {
+                portals.insert((x, y), Portal { target, normal });
+            }
+        }
+        // Un portal cuyo destino no tiene cara libre se queda como pared
+        let linked: Vec<(usize, usize)> = portals.iter().filter(|(_, p)| portals.contains_key(&p.target)).map(|(&cell, _)| cell).collect();
+        portals.retain(|cell, _| linked.contains(cell));
+
+        Teleporters { pads, portals, last_cell: (usize::MAX, usize::MAX) }
+    }
+
+    pub fn is_linked_portal(&self, x: usize, y: usize) -> bool {
+        self.portals.contains_key(&(x, y))
+    }
+
+    // Traslada un punto que entra en el portal (x, y) por la cara con normal `normal_in`
+    // al otro lado del portal enlazado. Devuelve el punto y el giro a aplicar a la dirección.
+    pub fn warp(&self, x: usize, y: usize, normal_in: (isize, isize), point: (f32, f32)) -> Option<((f32, f32), f32)> {
+        let portal = self.portals.get(&(x, y))?;
+        let exit = self.portals.get(&portal.target)?;
+
+        // Entrar contra la cara de origen equivale a salir por la cara libre del destino
+        let rotation = (exit.normal.1 as f32).atan2(exit.normal.0 as f32) - (-normal_in.1 as f32).atan2(-normal_in.0 as f32);
+        let (cos_r, sin_r) = (rotation.cos(), rotation.sin());
+
+        let (face_x, face_y) = Portal::face(x, y, normal_in);
+        let (exit_x, exit_y) = Portal::face(portal.target.0, portal.target.1, exit.normal);
+        let (rel_x, rel_y) = (point.0 - face_x, point.1 - face_y);
+
+        Some(((exit_x + rel_x * cos_r - rel_y * sin_r, exit_y + rel_x * sin_r + rel_y * cos_r), rotation))
+    }
+
+    // Teletransporta al jugador si acaba de pisar un teletransportador o de cruzar un portal
+    pub fn update(&mut self, player: &mut Player) -> bool {
+        let cell = (player.pos.x.floor() as usize, player.pos.y.floor() as usize);
+
+        if let Some(portal) = self.portals.get(&cell) {
+            if let Some(((x, y), rotation)) = self.warp(cell.0, cell.1, portal.normal, (player.pos.x, player.pos.y)) {
+                player.pos.x = x;
+                player.pos.y = y;
+                player.a = (player.a + rotation).rem_euclid(std::f32::consts::TAU);
+                self.last_cell = (x.floor() as usize, y.floor() as usize);
+                return true;
+            }
+        }
+
+        if cell == self.last_cell {
+            return false;
+        }
+        self.last_cell = cell;
+
+        let Some(pad) = self.pads.get(&cell) else {
+            return false;
+        };
+        player.pos.x = pad.target.0 as f32 + 0.5;
+        player.pos.y = pad.target.1 as f32 + 0.5;
+        if let Some(facing) = pad.facing {
+            player.a = facing;
+        }
+        self.last_cell = pad.target;
+        true
+    }
+}
@@ -1,19 +1,131 @@
 use image::{DynamicImage, RgbaImage};
-use std::path::Path;
 
+use crate::color;
+
+// Qué hacer con las coordenadas fuera de 0.0..1.0: repetir la textura (paredes, suelo,
+// cielo) o quedarse en el borde (sprites, calcomanías, iconos)
+#[derive(Clone, Copy, PartialEq)]
+pub enum Wrap {
+    Repeat,
+    Clamp,
+}
+
+#[derive(Clone)]
 pub struct Texture {
     pub width: usize,
     pub height: usize,
     pub data: Vec<u32>,
+    pub mips: Vec<Texture>, // Versiones reducidas a la mitad, de 1/2 hasta 1 texel de lado
 }
 
 impl Texture {
-    pub fn load_from_file<P: AsRef<Path>>(filename: P) -> Result<Texture, String> {
-        let img = match image::open(filename) {
-            Ok(img) => img,
-            Err(e) => return Err(format!("Failed to load texture: {}", e)),
-        };
+    pub fn new(width: usize, height: usize, data: Vec<u32>) -> Texture {
+        let mut texture = Texture { data, width, height, mips: Vec::new() };
+        texture.mips = texture.build_mips();
+        texture
+    }
+
+    // Cada nivel promedia bloques de 2x2 del anterior. Los texels transparentes no
+    // cuentan para el color, y el bloque solo es transparente si lo es al menos la mitad.
+    fn build_mips(&self) -> Vec<Texture> {
+        let mut mips: Vec<Texture> = Vec::new();
+        let (mut width, mut height) = (self.width, self.height);
+        while width > 1 && height > 1 {
+            let source = mips.last().unwrap_or(self);
+            let (next_width, next_height) = (width / 2, height / 2);
+            let mut data = Vec::with_capacity(next_width * next_height);
+            for y in 0..next_height {
+                for x in 0..next_width {
+                    let mut sum = [0u32; 3];
+                    let mut opaque = 0;
+                    for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        let texel = source.get_pixel(x * 2 + dx, y * 2 + dy);
+                        if texel >> 24 != 0 {
+                            sum[0] += (texel >> 16) & 0xFF;
+                            sum[1] += (texel >> 8) & 0xFF;
+                            sum[2] += texel & 0xFF;
+                            opaque += 1;
+                        }
+                    }
+                    data.push(if opaque >= 2 {
+                        0xFF000000 | ((sum[0] / opaque) << 16) | ((sum[1] / opaque) << 8) | (sum[2] / opaque)
+                    } else {
+                        0
+                    });
+                }
+            }
+            mips.push(Texture { width: next_width, height: next_height, data, mips: Vec::new() });
+            (width, height) = (next_width, next_height);
+        }
+        mips
+    }
+
+    // Rectángulo de la textura que se lee como si fuera una textura entera, sin copiarlo
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> Result<TextureView<'_>, String> {
+        if width == 0 || height == 0 || x + width > self.width || y + height > self.height {
+            return Err(format!("Region {}x{} at ({}, {}) is outside the {}x{} image", width, height, x, y, self.width, self.height));
+        }
+        Ok(TextureView { texture: self, x, y, width, height })
+    }
+
+    fn whole(&self) -> TextureView<'_> {
+        TextureView { texture: self, x: 0, y: 0, width: self.width, height: self.height }
+    }
+
+    // Copia un rectángulo como textura propia (un fotograma de una hoja, una región de un atlas)
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Result<Texture, String> {
+        Ok(self.view(x, y, width, height)?.to_texture())
+    }
+
+    // Hace transparentes los texels del color `key` (0xRRGGBB), para imágenes sin canal alfa
+    pub fn with_color_key(self, key: u32) -> Texture {
+        let data = self.data.into_iter().map(|texel| if texel & 0xFFFFFF == key & 0xFFFFFF { 0 } else { texel }).collect();
+        Texture::new(self.width, self.height, data)
+    }
+
+    // Multiplica cada texel por `tint` (0xRRGGBB), p. ej. para teñir de azul una pared helada
+    pub fn tinted(self, tint: u32) -> Texture {
+        let light = color::channels(tint).map(|channel| channel + 1);
+        let data = self.data.into_iter().map(|texel| color::modulate(texel, light)).collect();
+        Texture::new(self.width, self.height, data)
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> u32 {
+        self.data[y * self.width + x]
+    }
+
+    // Nivel de detalle `level` (0 es la textura completa); se queda en el más pequeño disponible
+    pub fn level(&self, level: usize) -> &Texture {
+        match level {
+            0 => self,
+            _ => self.mips.get(level - 1).or(self.mips.last()).unwrap_or(self),
+        }
+    }
 
+    // Nivel cuyo tamaño se parece más a los píxeles de pantalla que ocupa la textura
+    pub fn mip_for(&self, screen_size: f32) -> usize {
+        let texels_per_pixel = self.height as f32 / screen_size.max(1.0);
+        if texels_per_pixel <= 1.0 {
+            0
+        } else {
+            (texels_per_pixel.log2() as usize).min(self.mips.len())
+        }
+    }
+
+    // Color en las coordenadas (u, v); ver `TextureView::sample`
+    pub fn sample(&self, u: f32, v: f32, wrap: Wrap, bilinear: bool) -> u32 {
+        self.whole().sample(u, v, wrap, bilinear)
+    }
+
+    // Imagen ya leída en memoria (PNG, JPEG...), p. ej. la de un recurso incluido en el ejecutable
+    pub fn load_from_memory(bytes: &[u8]) -> Result<Texture, String> {
+        match image::load_from_memory(bytes) {
+            Ok(img) => Ok(Texture::from_image(img)),
+            Err(e) => Err(format!("Failed to load texture: {}", e)),
+        }
+    }
+
+    fn from_image(img: DynamicImage) -> Texture {
         let rgba = img.to_rgba8();
         let width = rgba.width() as usize;
         let height = rgba.height() as usize;
@@ -21,14 +133,67 @@ impl Texture {
         let mut data = Vec::with_capacity(width * height);
         for pixel in rgba.pixels() {
             let rgba = pixel.0;
-            let color = ((rgba[0] as u32) << 16) | ((rgba[1] as u32) << 8) | (rgba[2] as u32);
+            // El alfa se conserva: las paredes con huecos saltan los texels con alfa 0
+            let color = ((rgba[3] as u32) << 24) | ((rgba[0] as u32) << 16) | ((rgba[1] as u32) << 8) | (rgba[2] as u32);
             data.push(color);
         }
 
-        Ok(Texture {
-            width,
-            height,
-            data,
-        })
+        Texture::new(width, height, data)
+    }
+}
+
+// Rectángulo de una textura (ver `Texture::view`)
+pub struct TextureView<'a> {
+    texture: &'a Texture,
+    x: usize,
+    y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl TextureView<'_> {
+    pub fn get_pixel(&self, x: usize, y: usize) -> u32 {
+        self.texture.data[(self.y + y) * self.texture.width + self.x + x]
+    }
+
+    pub fn to_texture(&self) -> Texture {
+        let data = (0..self.height).flat_map(|y| (0..self.width).map(move |x| self.get_pixel(x, y))).collect();
+        Texture::new(self.width, self.height, data)
+    }
+
+    // Color en las coordenadas (u, v), que fuera de 0.0..1.0 se repiten o se quedan en el borde
+    // según `wrap`. Con `bilinear` mezcla los cuatro texels más cercanos, salvo junto a un
+    // hueco transparente.
+    pub fn sample(&self, u: f32, v: f32, wrap: Wrap, bilinear: bool) -> u32 {
+        let texel = |value: f32, size: usize| match wrap {
+            Wrap::Repeat => (value.floor() as isize).rem_euclid(size as isize) as usize,
+            Wrap::Clamp => (value.max(0.0) as usize).min(size - 1),
+        };
+        let x = u * self.width as f32;
+        let y = v * self.height as f32;
+        if !bilinear {
+            return self.get_pixel(texel(x, self.width), texel(y, self.height));
+        }
+
+        // Centros de texel en coordenadas enteras + 0.5
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (color::to_fixed(x - x0), color::to_fixed(y - y0));
+        let (left, right) = (texel(x0, self.width), texel(x0 + 1.0, self.width));
+        let (top, bottom) = (texel(y0, self.height), texel(y0 + 1.0, self.height));
+
+        let texels = [
+            self.get_pixel(left, top),
+            self.get_pixel(right, top),
+            self.get_pixel(left, bottom),
+            self.get_pixel(right, bottom),
+        ];
+        if texels.iter().any(|&texel| texel >> 24 == 0) {
+            let nearest = (if fy < 128 { 0 } else { 2 }) + if fx < 128 { 0 } else { 1 };
+            return texels[nearest];
+        }
+        let upper = color::lerp(texels[0], texels[1], fx);
+        let lower = color::lerp(texels[2], texels[3], fx);
+        color::lerp(upper, lower, fy)
     }
 }
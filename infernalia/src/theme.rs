@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+// Paquetes de temas: una carpeta o un .zip con texturas, sonidos, música y fuentes que
+// sustituyen a los recursos por defecto del mismo nombre (p. ej. "textures/walls.jpg" o
+// "music/ambience.mp3"). En la raíz del paquete va un manifiesto, theme.ron o theme.json:
+//
+//     (
+//         name: "Círculo helado",
+//         author: Some("Alguien"),
+//         description: Some("El noveno círculo, congelado"),
+//         files: {
+//             "music/ambience.mp3": "audio/viento.ogg",
+//         },
+//     )
+//
+// `files` es opcional y sirve para usar otro nombre de archivo dentro del paquete. Los temas
+// se eligen con `--theme nombre` (busca en assets/themes) o `--theme ruta`.
+
+const MANIFESTS: [&str; 2] = ["theme.ron", "theme.json"];
+pub const THEMES_FOLDER: &str = "themes";
+
+#[derive(Deserialize)]
+struct Manifest {
+    name: String,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    files: HashMap<String, String>,
+}
+
+enum Source {
+    Folder(PathBuf),
+    Zip(HashMap<String, Vec<u8>>), // Contenido de cada archivo, por ruta dentro del paquete
+}
+
+pub struct Theme {
+    pub name: String,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    files: HashMap<String, String>,
+    source: Source,
+}
+
+impl Theme {
+    pub fn load(path: &Path) -> Result<Theme, String> {
+        let source = if path.is_dir() {
+            Source::Folder(path.to_path_buf())
+        } else {
+            Source::Zip(read_zip(path)?)
+        };
+
+        let (manifest_name, text) = MANIFESTS
+            .iter()
+            .find_map(|name| source.read(name).map(|bytes| (*name, bytes)))
+            .ok_or_else(|| format!("{} has no theme.ron or theme.json", path.display()))?;
+        let text = String::from_utf8_lossy(&text);
+        let manifest: Manifest = if manifest_name.ends_with(".ron") {
+            ron::from_str(&text).map_err(|e| format!("Invalid {} in {}: {}", manifest_name, path.display(), e))?
+        } else {
+            serde_json::from_str(&text).map_err(|e| format!("Invalid {} in {}: {}", manifest_name, path.display(), e))?
+        };
+
+        Ok(Theme {
+            name: manifest.name,
+            author: manifest.author,
+            description: manifest.description,
+            files: manifest.files,
+            source,
+        })
+    }
+
+    // Contenido del recurso `name` en el paquete, si lo sustituye
+    pub fn read(&self, name: &str) -> Option<Vec<u8>> {
+        self.source.read(self.files.get(name).map_or(name, String::as_str))
+    }
+
+    // Recursos de la carpeta `folder` que trae el paquete, como "textures/walls.png"
+    pub fn list(&self, folder: &str) -> Vec<String> {
+        let prefix = format!("{}/", folder.trim_end_matches('/'));
+        let mut names: Vec<String> = match &self.source {
+            Source::Folder(root) => fs::read_dir(root.join(folder))
+                .map(|entries| entries.flatten().filter_map(|entry| entry.file_name().to_str().map(|file| format!("{}{}", prefix, file))).collect())
+                .unwrap_or_default(),
+            Source::Zip(entries) => entries.keys().filter(|name| name.strip_prefix(&prefix).is_some_and(|file| !file.contains('/'))).cloned().collect(),
+        };
+        // Los archivos renombrados en el manifiesto cuentan con su nombre de recurso
+        names.extend(self.files.keys().filter(|name| name.starts_with(&prefix)).cloned());
+        names
+    }
+}
+
+impl Source {
+    fn read(&self, name: &str) -> Option<Vec<u8>> {
+        match self {
+            Source::Folder(root) => fs::read(root.join(name)).ok(),
+            Source::Zip(entries) => entries.get(name).cloned(),
+        }
+    }
+}
+
+// Lee todo el zip en memoria. Si el manifiesto está dentro de una carpeta (el zip de una
+// carpeta entera), las rutas se toman relativas a ella.
+fn read_zip(path: &Path) -> Result<HashMap<String, Vec<u8>>, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid zip {}: {}", path.display(), e))?;
+
+    let mut entries = HashMap::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| format!("Invalid zip {}: {}", path.display(), e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).map_err(|e| format!("Failed to read {} in {}: {}", entry.name(), path.display(), e))?;
+        entries.insert(entry.name().replace('\\', "/"), bytes);
+    }
+
+    let manifest = entries.keys().find(|name| MANIFESTS.iter().any(|manifest| *name == manifest || name.ends_with(&format!("/{}", manifest)))).cloned();
+    let prefix = manifest.and_then(|name| name.rfind('/').map(|slash| name[..=slash].to_string()));
+    if let Some(prefix) = prefix {
+        entries = entries.into_iter().filter_map(|(name, bytes)| Some((name.strip_prefix(&prefix)?.to_string(), bytes))).collect();
+    }
+    Ok(entries)
+}
+
+// Ruta del tema `name`: una ruta directa, o una carpeta o .zip dentro de `themes_folder`
+pub fn find(themes_folder: &Path, name: &str) -> Option<PathBuf> {
+    let direct = PathBuf::from(name);
+    if direct.exists() {
+        return Some(direct);
+    }
+    [themes_folder.join(name), themes_folder.join(format!("{}.zip", name))].into_iter().find(|path| path.exists())
+}
+
+// Nombres de los temas instalados en `themes_folder`
+pub fn available(themes_folder: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(themes_folder)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir() || path.extension().is_some_and(|extension| extension == "zip"))
+                .filter_map(|path| path.file_stem().and_then(|stem| stem.to_str()).map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
@@ -0,0 +1,55 @@
+use crate::texture::Texture;
+
+// Texturas con huecos (texels con alfa 0) para las paredes que dejan ver lo
+// que hay detrás: barrotes, rejillas, ventanas rotas y alambrado.
+
+const SIZE: usize = 64;
+const IRON: u32 = 0xFF3A3A40;
+
+pub fn is_transparent(color: u32) -> bool {
+    color >> 24 == 0
+}
+
+fn pattern(solid: impl Fn(usize, usize) -> bool) -> Texture {
+    let mut data = Vec::with_capacity(SIZE * SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            data.push(if solid(x, y) { IRON } else { 0 });
+        }
+    }
+    Texture::new(SIZE, SIZE, data)
+}
+
+// Barrotes verticales con dos travesaños
+pub fn iron_bars() -> Texture {
+    pattern(|x, y| (6..10).contains(&(x % 16)) || (4..8).contains(&y) || (56..60).contains(&y))
+}
+
+pub fn grate() -> Texture {
+    pattern(|x, y| x % 12 < 3 || y % 12 < 3)
+}
+
+// Rombos en diagonal
+pub fn chain_link() -> Texture {
+    pattern(|x, y| (x + y) % 12 < 2 || (x + SIZE - y) % 12 < 2)
+}
+
+// La pared normal con un agujero de bordes irregulares en el centro
+pub fn broken_window(wall_texture: &Texture) -> Texture {
+    let mut data = wall_texture.data.clone();
+    let (width, height) = (wall_texture.width, wall_texture.height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let u = x as f32 / width as f32 - 0.5;
+            let v = y as f32 / height as f32 - 0.5;
+            let angle = v.atan2(u);
+            let jagged = 0.3 + 0.08 * (angle * 7.0).sin() + 0.04 * (angle * 13.0).sin();
+            if (u * u + v * v).sqrt() < jagged {
+                data[y * width + x] = 0;
+            }
+        }
+    }
+
+    Texture::new(width, height, data)
+}
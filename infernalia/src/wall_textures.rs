@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use crate::atlas::Atlas;
+use crate::animation::{flesh, ice, lava, mud, AnimatedTexture};
+use crate::door::door_texture;
+use crate::map::{key_color, key_name};
+use crate::texture::Texture;
+use crate::transparent::{broken_window, chain_link, grate, iron_bars};
+
+// Texturas de los tipos de celda especiales, por carácter del mapa: paredes y
+// suelos como la lava, el fango o el hielo. Las paredes que no tienen una propia usan la textura de pared normal.
+// Las que están en el atlas (ver `atlas_name`) sustituyen a las generadas aquí.
+pub struct WallTextures {
+    textures: HashMap<char, AnimatedTexture>,
+}
+
+impl WallTextures {
+    pub fn new(wall_texture: &Texture, atlas: &Atlas, seed: u32) -> Self {
+        let mut textures = HashMap::new();
+        textures.insert('|', AnimatedTexture::still(iron_bars()));
+        textures.insert('+', AnimatedTexture::still(grate()));
+        textures.insert('w', AnimatedTexture::still(broken_window(wall_texture)));
+        textures.insert('x', AnimatedTexture::still(chain_link()));
+        textures.insert('D', AnimatedTexture::still(door_texture(0x2E2E34)));
+        for door in ['R', 'B', 'Y'] {
+            textures.insert(door, AnimatedTexture::still(door_texture(key_color(door))));
+        }
+        textures.insert('F', flesh());
+        textures.insert('~', lava(seed));
+        textures.insert(';', mud(seed));
+        textures.insert('=', ice(seed));
+
+        for (&cell, texture) in textures.iter_mut() {
+            if let Some(replacement) = atlas.animated_texture(&atlas_name(cell)) {
+                *texture = replacement;
+            }
+        }
+
+        WallTextures { textures }
+    }
+
+    pub fn get(&self, cell: char) -> Option<&AnimatedTexture> {
+        self.textures.get(&cell)
+    }
+
+    // Avanza todas las texturas animadas
+    pub fn update(&mut self, delta_time: f32) {
+        for texture in self.textures.values_mut() {
+            texture.update(delta_time);
+        }
+    }
+}
+
+fn atlas_name(cell: char) -> String {
+    match cell {
+        '|' => "bars".to_string(),
+        '+' => "grate".to_string(),
+        'w' => "window".to_string(),
+        'x' => "chain_link".to_string(),
+        'D' => "door".to_string(),
+        'F' => "flesh".to_string(),
+        '~' => "lava".to_string(),
+        ';' => "mud".to_string(),
+        '=' => "ice".to_string(),
+        door => format!("door_{}", key_name(door)),
+    }
+}
@@ -0,0 +1,64 @@
+use crate::decal::Decals;
+use crate::door::Doors;
+use crate::map::{is_door, is_partial_wall, is_portal, is_wall};
+use crate::player::Player;
+use crate::pushwall::PushWalls;
+use crate::teleport::Teleporters;
+
+const DEFAULT_MIRROR_DEPTH: u32 = 3;
+
+// Partes del nivel que cambian durante la partida o enlazan unas celdas con otras
+// (puertas, paredes secretas, teletransportadores y portales) y adornos como las calcomanías.
+// El mapa de caracteres sigue siendo la referencia; esto guarda el estado de cada pieza.
+pub struct World {
+    pub doors: Doors,
+    pub push_walls: PushWalls,
+    pub teleporters: Teleporters,
+    pub decals: Decals,
+    pub mirror_depth: u32, // Rebotes que sigue un rayo entre espejos (`:mirror_depth n` en el nivel)
+}
+
+impl World {
+    // `directives` son las líneas de metadatos del nivel (ver `map::load_level`)
+    pub fn from_level(maze: &[Vec<char>], directives: &[Vec<String>]) -> Self {
+        World {
+            doors: Doors::from_maze(maze),
+            push_walls: PushWalls::new(),
+            teleporters: Teleporters::from_level(maze, directives),
+            decals: Decals::from_level(directives),
+            mirror_depth: directives
+                .iter()
+                .find(|words| words[0] == "mirror_depth")
+                .and_then(|words| words.get(1)?.parse().ok())
+                .unwrap_or(DEFAULT_MIRROR_DEPTH),
+        }
+    }
+
+    // Usar (E): abre la puerta o empuja la pared secreta que el jugador tiene delante
+    pub fn use_in_front(&mut self, player: &Player, maze: &mut [Vec<char>]) {
+        if !self.doors.try_open(player) {
+            self.push_walls.try_push(player, maze);
+        }
+    }
+
+    // Avanza puertas y paredes secretas; devuelve true si la forma del mapa cambió
+    pub fn update(&mut self, delta_time: f32, player: &Player, maze: &mut [Vec<char>]) -> bool {
+        self.doors.update(delta_time, player);
+        self.push_walls.update(delta_time, maze)
+    }
+
+    // Si la celda (x, y) entera impide el paso al jugador
+    pub fn blocks(&self, maze: &[Vec<char>], x: usize, y: usize) -> bool {
+        let cell = maze[y][x];
+        // Las puertas solo bloquean mientras no estén completamente abiertas
+        if is_door(cell) {
+            self.doors.is_blocking(x, y)
+        } else if is_portal(cell) {
+            // Se cruza hacia el portal enlazado
+            !self.teleporters.is_linked_portal(x, y)
+        } else {
+            // Contra las paredes parciales se choca con su figura (ver `Player::is_collision`)
+            is_wall(cell) && !is_partial_wall(cell)
+        }
+    }
+}